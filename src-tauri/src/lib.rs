@@ -1,14 +1,163 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use percent_encoding::percent_decode_str;
+use tauri::Manager;
+
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+// Lets the frontend ask before it overwrites an existing recording
+#[tauri::command]
+fn file_exists(path: &str) -> bool {
+    Path::new(path).exists()
+}
+
+// `take.webm` → `take (1).webm`, `take (2).webm`, … the first name not already on disk
+#[tauri::command]
+fn next_free_path(path: &str) -> String {
+    let original = Path::new(path);
+    if !original.exists() {
+        return path.to_string();
+    }
+    let stem = original
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("recording");
+    let ext = original.extension().and_then(|s| s.to_str());
+    let parent = original.parent().map(Path::to_path_buf).unwrap_or_default();
+    (1..)
+        .map(|n| {
+            let name = match ext {
+                Some(ext) => format!("{} ({}).{}", stem, n, ext),
+                None => format!("{} ({})", stem, n),
+            };
+            parent.join(name)
+        })
+        .find(|candidate: &PathBuf| !candidate.exists())
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+// Where a new recording goes by default: the user's Videos folder, else Downloads
+#[tauri::command]
+fn default_recording_path(app: tauri::AppHandle, filename: &str) -> Result<String, String> {
+    let dir = app
+        .path()
+        .video_dir()
+        .or_else(|_| app.path().download_dir())
+        .map_err(|e| e.to_string())?;
+    Ok(dir.join(filename).to_string_lossy().into_owned())
+}
+
+// Write a recording to disk, replacing anything already at `path`; the frontend
+// checks `file_exists` first. Returns the path written.
+// The bytes arrive as the raw invoke body, not JSON, so a long take isn't blown
+// up into an array of numbers; the path comes percent-encoded in a `path` header.
+#[tauri::command]
+fn save_recording(request: tauri::ipc::Request<'_>) -> Result<String, String> {
+    let tauri::ipc::InvokeBody::Raw(bytes) = request.body() else {
+        return Err("expected the recording as raw bytes".to_string());
+    };
+    let path = request
+        .headers()
+        .get("path")
+        .and_then(|value| value.to_str().ok())
+        .ok_or("missing the path to save to")?;
+    let path = percent_decode_str(path)
+        .decode_utf8()
+        .map_err(|e| format!("bad path: {}", e))?;
+    let target = Path::new(path.as_ref());
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("couldn't create {}: {}", parent.display(), e))?;
+    }
+    fs::write(target, bytes).map_err(|e| format!("couldn't write {}: {}", target.display(), e))?;
+    Ok(path.into_owned())
+}
+
+// Read a saved recording back in, for takes kept by path across restarts. Sent as
+// raw bytes for the same reason `save_recording` takes them that way.
+#[tauri::command]
+fn read_recording(path: &str) -> Result<tauri::ipc::Response, String> {
+    fs::read(path)
+        .map(tauri::ipc::Response::new)
+        .map_err(|e| format!("couldn't read {}: {}", path, e))
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet])
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            file_exists,
+            next_free_path,
+            default_recording_path,
+            save_recording,
+            read_recording
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a fresh, empty directory per test under the system temp dir
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("born-edited-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn path_str(path: &Path) -> String {
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn a_free_name_is_used_as_is() {
+        let dir = scratch_dir("free");
+        let path = path_str(&dir.join("take.webm"));
+        assert_eq!(next_free_path(&path), path);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn taken_names_get_the_next_number_before_the_extension() {
+        let dir = scratch_dir("numbered");
+        fs::write(dir.join("take.webm"), b"").unwrap();
+        let path = path_str(&dir.join("take.webm"));
+        assert_eq!(next_free_path(&path), path_str(&dir.join("take (1).webm")));
+        fs::write(dir.join("take (1).webm"), b"").unwrap();
+        assert_eq!(next_free_path(&path), path_str(&dir.join("take (2).webm")));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn names_without_an_extension_get_a_plain_suffix() {
+        let dir = scratch_dir("no-ext");
+        fs::write(dir.join("take"), b"").unwrap();
+        assert_eq!(
+            next_free_path(&path_str(&dir.join("take"))),
+            path_str(&dir.join("take (1)"))
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn dotfiles_keep_their_whole_name() {
+        let dir = scratch_dir("dotfile");
+        fs::write(dir.join(".take"), b"").unwrap();
+        assert_eq!(
+            next_free_path(&path_str(&dir.join(".take"))),
+            path_str(&dir.join(".take (1)"))
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,242 +1,2669 @@
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::{MediaRecorder, MediaStream, 
-                MediaStreamConstraints, HtmlElement, Url, SpeechRecognition, SpeechRecognitionEvent};
+use web_sys::{AnalyserNode, AudioContext, CanvasRenderingContext2d, HtmlCanvasElement,
+                HtmlVideoElement, MediaStream, MediaStreamTrack,
+                HtmlElement, Url, SpeechRecognitionEvent};
 use yew::prelude::*;
-use std::fmt::{self, Display};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
 use gloo_timers::callback::Interval;
 
+use crate::recorder::{
+    enumerate_inputs, probe_power_efficient, video_resolution, video_frame_rate, recording_filename, segment_filename, request_media_stream, resolve_recording_type,
+    js_error_message, merge_chunks, preflight_line, poll_status, query_access, run_self_test, stop_tracks, swap_camera, trim_chunks, Access, CameraSwap, BitratePreset, CaptureConfig, EncoderReport, InputDevice, MediaError, Recorder, RecordingStatus, Resolution, FrameRate,
+    SelfTestState, Take, FormatSupport, SELF_TEST_SECS, run_mic_test, MicTestState, MIC_TEST_SECS,
+};
+use crate::take_store::{self, near_quota, plan_sync, Kept, Stored, StoredTake, TakeStorage};
+use crate::speech::{
+    ActiveClock, Recognizer, SessionWords, WpmTracker, DEFAULT_MIN_CONFIDENCE, SPEECH_LANGUAGES, SPEECH_MAX_QUICK_ENDS, SPEECH_QUICK_END_MS,
+};
+
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
-    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+    // rejects with the command's error string
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    // `invoke` with a raw payload (an ArrayBuffer or typed array) and request options
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke, catch)]
+    async fn invoke_raw(cmd: &str, payload: JsValue, options: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct FilenameArgs<'a> {
+    filename: &'a str,
+}
+
+#[derive(Serialize)]
+struct PathArgs<'a> {
+    path: &'a str,
+}
+
+// RMS level below which the mic counts as silent, for a 0–100 sensitivity
+// (higher sensitivity → quieter speech still counts as voice)
+fn vad_threshold(sensitivity: u32) -> f32 {
+    let s = sensitivity.min(100) as f32 / 100.0;
+    0.002 + (1.0 - s) * 0.05
+}
+
+// root-mean-square of one analyser time-domain frame
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+// how often the mic level meter is refreshed
+const LEVEL_METER_MS: u32 = 100;
+// the meter spans this many dB below full scale
+const LEVEL_METER_FLOOR_DB: f32 = 60.0;
+
+// peak of one analyser frame, mapped from dBFS onto 0..=1 for the meter bar
+fn meter_level(samples: &[f32]) -> f32 {
+    let peak = samples.iter().fold(0f32, |m, s| m.max(s.abs()));
+    if peak <= 0.0 {
+        return 0.0;
+    }
+    ((20.0 * peak.log10() + LEVEL_METER_FLOOR_DB) / LEVEL_METER_FLOOR_DB).clamp(0.0, 1.0)
+}
+
+// teleprompter line-height, as a multiple of the font size
+const PROMPTER_LINE_HEIGHT: f64 = 1.4;
+const PROMPTER_FONT_RANGE: (u32, u32) = (12, 96);
+// how many lines the prompter shows at once; two keeps the next line in view
+const PROMPTER_LINES_RANGE: (u32, u32) = (2, 20);
+// the prompter's padding, top and bottom
+const PROMPTER_PADDING_PX: u32 = 8;
+// fullscreen is read from further away, so the text grows by this factor
+const FULLSCREEN_FONT_SCALE: u32 = 2;
+// longest lead-in the settings allow
+const LEAD_IN_MAX_SECS: u32 = 10;
+// how long the scroll timer waits after a wheel or arrow-key nudge
+const NUDGE_HOLD_MS: f64 = 2000.0;
+
+// How far to scroll per spoken word: the laid-out text's full height shared
+// evenly by its words, so wrapping and font size are already accounted for
+fn pixels_per_word(scroll_height_px: f64, word_count: usize) -> f64 {
+    if word_count == 0 {
+        return 0.0;
+    }
+    scroll_height_px / word_count as f64
+}
+
+// The script's lines as written, each word paired with its index among all the
+// script's words (the numbering `split_whitespace` over the whole script gives)
+fn script_lines(script: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut next = 0;
+    script
+        .lines()
+        .map(|line| {
+            line.split_whitespace()
+                .map(|word| {
+                    next += 1;
+                    (next - 1, word)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Line-by-line mode: the scroll offset that brings the line holding `word` to the
+// top, read off the rendered word spans rather than guessed from font metrics
+fn line_top(tele_el: &HtmlElement, word: usize) -> Option<f64> {
+    let spans = tele_el.get_elements_by_tag_name("span");
+    let first = spans.item(0)?.dyn_into::<HtmlElement>().ok()?;
+    let span = spans.item(word as u32)?.dyn_into::<HtmlElement>().ok()?;
+    Some((span.offset_top() - first.offset_top()) as f64)
+}
+
+// The vertical middle of every word span, in the prompter's scroll coordinates
+fn word_centers(tele_el: &HtmlElement) -> Vec<f64> {
+    let spans = tele_el.get_elements_by_tag_name("span");
+    (0..spans.length())
+        .filter_map(|i| spans.item(i)?.dyn_into::<HtmlElement>().ok())
+        .map(|span| span.offset_top() as f64 + span.offset_height() as f64 / 2.0)
+        .collect()
+}
+
+// The scroll offset that puts the reader's position `acc` (words, fractional) in
+// the middle of a `viewport_px` tall prompter, gliding between neighbouring words
+fn centered_top(centers: &[f64], acc: f64, viewport_px: f64) -> Option<f64> {
+    let last = centers.len().checked_sub(1)?;
+    let word = (acc.max(0.0) as usize).min(last);
+    let next = centers[(word + 1).min(last)];
+    let frac = (acc - word as f64).clamp(0.0, 1.0);
+    let center = centers[word] + (next - centers[word]) * frac;
+    Some((center - viewport_px / 2.0).max(0.0))
+}
+
+// keep the prompter moving briefly after the voice drops, so it doesn't stutter between words
+const VAD_HANGOVER_MS: f64 = 300.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum OverlayAlign {
+    Left,
+    Center,
+    Right,
+}
+
+// Name/title strip composited into the bottom of the recording
+#[derive(Clone, PartialEq)]
+struct LowerThird {
+    enabled: bool,
+    title: String,
+    subtitle: String,
+    font_family: String,
+    font_px: u32,
+    align: OverlayAlign,
+    // fade in at `in_secs` and out at `out_secs` into the take; otherwise stay up
+    animate: bool,
+    in_secs: f64,
+    out_secs: f64,
+}
+
+impl Default for LowerThird {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            title: String::new(),
+            subtitle: String::new(),
+            font_family: "sans-serif".into(),
+            font_px: 32,
+            align: OverlayAlign::Left,
+            animate: false,
+            in_secs: 2.0,
+            out_secs: 10.0,
+        }
+    }
+}
+
+const LOWER_THIRD_FADE_SECS: f64 = 0.5;
+const COMPOSITOR_FPS: u32 = 30;
+
+// opacity of an animated lower third `elapsed` seconds into the take;
+// an `out_secs` at or before `in_secs` means it never leaves
+fn lower_third_alpha(elapsed: f64, in_secs: f64, out_secs: f64) -> f64 {
+    let fade_in = ((elapsed - in_secs) / LOWER_THIRD_FADE_SECS).clamp(0.0, 1.0);
+    if out_secs <= in_secs {
+        return fade_in;
+    }
+    let fade_out = ((out_secs - elapsed) / LOWER_THIRD_FADE_SECS).clamp(0.0, 1.0);
+    fade_in.min(fade_out)
+}
+
+fn draw_lower_third(ctx: &CanvasRenderingContext2d, width: f64, height: f64, cfg: &LowerThird, alpha: f64) {
+    if alpha <= 0.0 || (cfg.title.is_empty() && cfg.subtitle.is_empty()) {
+        return;
+    }
+    let title_px = cfg.font_px as f64;
+    let sub_px = (title_px * 0.6).round();
+    let pad = title_px * 0.4;
+    let bar_h = title_px + if cfg.subtitle.is_empty() { 0.0 } else { sub_px + pad * 0.5 } + pad * 2.0;
+    let bar_y = height - bar_h - height * 0.08;
+
+    ctx.set_font(&format!("bold {}px {}", title_px, cfg.font_family));
+    let title_w = ctx.measure_text(&cfg.title).map(|m| m.width()).unwrap_or(0.0);
+    ctx.set_font(&format!("{}px {}", sub_px, cfg.font_family));
+    let sub_w = ctx.measure_text(&cfg.subtitle).map(|m| m.width()).unwrap_or(0.0);
+    let bar_w = title_w.max(sub_w) + pad * 2.0;
+    let bar_x = match cfg.align {
+        OverlayAlign::Left => width * 0.05,
+        OverlayAlign::Center => (width - bar_w) / 2.0,
+        OverlayAlign::Right => width * 0.95 - bar_w,
+    };
+
+    ctx.set_global_alpha(alpha);
+    ctx.set_fill_style_str("rgba(0, 0, 0, 0.65)");
+    ctx.fill_rect(bar_x, bar_y, bar_w, bar_h);
+    ctx.set_fill_style_str("white");
+    ctx.set_text_baseline("top");
+    ctx.set_font(&format!("bold {}px {}", title_px, cfg.font_family));
+    let _ = ctx.fill_text(&cfg.title, bar_x + pad, bar_y + pad);
+    if !cfg.subtitle.is_empty() {
+        ctx.set_font(&format!("{}px {}", sub_px, cfg.font_family));
+        let _ = ctx.fill_text(&cfg.subtitle, bar_x + pad, bar_y + pad * 1.5 + title_px);
+    }
+    ctx.set_global_alpha(1.0);
+}
+
+// Time info burned into the top-right corner of the recording
+#[derive(Clone, Copy, PartialEq)]
+enum StampMode {
+    None,
+    // wall-clock date and time
+    Timestamp,
+    // HH:MM:SS:FF from the start of the take, for syncing in an editor
+    Timecode,
+    Both,
+}
+
+impl StampMode {
+    fn shows_timestamp(self) -> bool {
+        matches!(self, StampMode::Timestamp | StampMode::Both)
+    }
+
+    fn shows_timecode(self) -> bool {
+        matches!(self, StampMode::Timecode | StampMode::Both)
+    }
+}
+
+// `elapsed_ms` into the take as HH:MM:SS:FF at `fps`
+fn format_timecode(elapsed_ms: f64, fps: u32) -> String {
+    let total_frames = (elapsed_ms.max(0.0) * fps as f64 / 1000.0).floor() as u64;
+    let fps = fps.max(1) as u64;
+    let frames = total_frames % fps;
+    let secs = total_frames / fps;
+    format!("{:02}:{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60, frames)
+}
+
+fn format_wall_clock(date: &js_sys::Date) -> String {
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        date.get_full_year(),
+        date.get_month() + 1,
+        date.get_date(),
+        date.get_hours(),
+        date.get_minutes(),
+        date.get_seconds()
+    )
+}
+
+fn draw_stamps(ctx: &CanvasRenderingContext2d, width: f64, height: f64, mode: StampMode, elapsed_ms: f64) {
+    let mut lines = Vec::new();
+    if mode.shows_timestamp() {
+        lines.push(format_wall_clock(&js_sys::Date::new_0()));
+    }
+    if mode.shows_timecode() {
+        lines.push(format_timecode(elapsed_ms, COMPOSITOR_FPS));
+    }
+    if lines.is_empty() {
+        return;
+    }
+    let font_px = (height / 24.0).round().max(12.0);
+    let pad = font_px * 0.4;
+    ctx.set_font(&format!("{}px monospace", font_px));
+    let text_w = lines
+        .iter()
+        .filter_map(|l| ctx.measure_text(l).ok())
+        .map(|m| m.width())
+        .fold(0.0, f64::max);
+    let box_w = text_w + pad * 2.0;
+    let box_h = lines.len() as f64 * font_px * 1.2 + pad * 2.0;
+    let x = width - box_w - width * 0.03;
+    let y = height * 0.03;
+
+    ctx.set_fill_style_str("rgba(0, 0, 0, 0.55)");
+    ctx.fill_rect(x, y, box_w, box_h);
+    ctx.set_fill_style_str("white");
+    ctx.set_text_baseline("top");
+    for (i, line) in lines.iter().enumerate() {
+        let _ = ctx.fill_text(line, x + pad, y + pad + i as f64 * font_px * 1.2);
+    }
+}
+
+// Paint camera frames plus the enabled overlays onto `canvas`; dropping the interval stops it
+fn start_compositor(
+    video: HtmlVideoElement,
+    canvas: HtmlCanvasElement,
+    lower_third: Rc<RefCell<LowerThird>>,
+    stamp: Rc<RefCell<StampMode>>,
+    rec_started: Rc<RefCell<Option<f64>>>,
+) -> Option<Interval> {
+    let ctx: CanvasRenderingContext2d = canvas.get_context("2d").ok()??.unchecked_into();
+    Some(Interval::new(1000 / COMPOSITOR_FPS, move || {
+        let (w, h) = (video.video_width(), video.video_height());
+        if w == 0 || h == 0 {
+            return;
+        }
+        if canvas.width() != w || canvas.height() != h {
+            canvas.set_width(w);
+            canvas.set_height(h);
+        }
+        let (w, h) = (w as f64, h as f64);
+        let _ = ctx.draw_image_with_html_video_element_and_dw_and_dh(&video, 0.0, 0.0, w, h);
+
+        let elapsed_ms = rec_started.borrow().map(|started| js_sys::Date::now() - started);
+        let cfg = lower_third.borrow();
+        if cfg.enabled {
+            // outside a take the preview shows the strip as it will look once it's in
+            let alpha = match elapsed_ms {
+                Some(ms) if cfg.animate => lower_third_alpha(ms / 1000.0, cfg.in_secs, cfg.out_secs),
+                _ => 1.0,
+            };
+            draw_lower_third(&ctx, w, h, &cfg, alpha);
+        }
+        draw_stamps(&ctx, w, h, *stamp.borrow(), elapsed_ms.unwrap_or(0.0));
+    }))
+}
+
+// A run of script text, optionally under a `# Heading` marker line
+#[derive(Clone, PartialEq, Debug)]
+struct ScriptSection {
+    heading: Option<String>,
+    body: String,
+}
+
+// Split the script on `#` marker lines into sections
+fn parse_script_sections(script: &str) -> Vec<ScriptSection> {
+    let mut sections = vec![ScriptSection { heading: None, body: String::new() }];
+    for line in script.lines() {
+        if let Some(heading) = line.trim_start().strip_prefix('#') {
+            sections.push(ScriptSection {
+                heading: Some(heading.trim_start_matches('#').trim().to_string()),
+                body: String::new(),
+            });
+        } else if let Some(current) = sections.last_mut() {
+            current.body.push_str(line);
+            current.body.push('\n');
+        }
+    }
+    sections.retain(|s| s.heading.is_some() || !s.body.trim().is_empty());
+    sections
 }
 
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Standalone, print-ready HTML for the script, with page numbers in the footer
+fn script_print_html(script: &str) -> String {
+    let mut body = String::new();
+    for section in parse_script_sections(script) {
+        if let Some(heading) = &section.heading {
+            body.push_str(&format!("<h2>{}</h2>", escape_html(heading)));
+        }
+        for para in section.body.split("\n\n").filter(|p| !p.trim().is_empty()) {
+            body.push_str(&format!("<p>{}</p>", escape_html(para.trim()).replace('\n', "<br>")));
+        }
+    }
+    format!(
+        r#"<!doctype html><html><head><meta charset="utf-8"><title>Script</title><style>
+@page {{ margin: 2cm; @bottom-center {{ content: "Page " counter(page) " of " counter(pages); }} }}
+body {{ font-family: Georgia, serif; font-size: 16pt; line-height: 1.5; }}
+h2 {{ font-family: sans-serif; break-after: avoid; }}
+</style></head><body>{}</body></html>"#,
+        body
+    )
+}
+
+// Print the script through a throwaway iframe; the print dialog offers "Save as PDF"
+fn print_script(script: &str) {
+    let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+        return;
+    };
+    let Some(body) = document.body() else {
+        return;
+    };
+    let Ok(iframe) = document.create_element("iframe") else {
+        return;
+    };
+    let iframe: web_sys::HtmlIFrameElement = iframe.unchecked_into();
+    let _ = iframe.set_attribute("style", "position: fixed; width: 0; height: 0; border: 0;");
+    iframe.set_srcdoc(&script_print_html(script));
+    let frame = iframe.clone();
+    gloo::events::EventListener::once(&iframe, "load", move |_| {
+        if let Some(win) = frame.content_window() {
+            if let Err(err) = win.print() {
+                gloo::console::error!("print failed:", err);
+            }
+        }
+        frame.remove();
+    })
+    .forget();
+    let _ = body.append_child(&iframe);
+}
+
+const SCRIPT_STORAGE_KEY: &str = "born-ed.script";
+const SETTINGS_STORAGE_KEY: &str = "born-ed.settings";
+// wait for a pause in typing before writing the script out
+const SCRIPT_SAVE_DEBOUNCE_MS: u32 = 500;
+
+// None when storage is blocked (e.g. some private-browsing modes); callers just skip persistence
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+// `None` until a script has been saved at least once
+fn load_script() -> Option<String> {
+    local_storage().and_then(|s| s.get_item(SCRIPT_STORAGE_KEY).ok().flatten())
+}
+
+fn save_script(script: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = if script.is_empty() {
+            storage.remove_item(SCRIPT_STORAGE_KEY)
+        } else {
+            storage.set_item(SCRIPT_STORAGE_KEY, script)
+        };
+    }
+}
+
+// Prompter colours. The high-contrast ones are solid even in overlay mode.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+enum PrompterTheme {
+    #[default]
+    Standard,
+    WhiteOnBlack,
+    BlackOnWhite,
+    YellowOnBlack,
+}
+
+impl PrompterTheme {
+    const ALL: [PrompterTheme; 4] =
+        [PrompterTheme::Standard, PrompterTheme::WhiteOnBlack, PrompterTheme::BlackOnWhite, PrompterTheme::YellowOnBlack];
+
+    fn label(self) -> &'static str {
+        match self {
+            PrompterTheme::Standard => "Standard",
+            PrompterTheme::WhiteOnBlack => "White on black",
+            PrompterTheme::BlackOnWhite => "Black on white",
+            PrompterTheme::YellowOnBlack => "Yellow on black",
+        }
+    }
+
+    // (text, background, current-word highlight); `None` keeps the standard look.
+    // Each highlight is picked to stand out against its own text and background
+    fn colors(self) -> Option<(&'static str, &'static str, &'static str)> {
+        match self {
+            PrompterTheme::Standard => None,
+            PrompterTheme::WhiteOnBlack => Some(("#fff", "#000", "rgba(255, 214, 0, 0.55)")),
+            PrompterTheme::BlackOnWhite => Some(("#000", "#fff", "rgba(0, 110, 255, 0.3)")),
+            PrompterTheme::YellowOnBlack => Some(("#ffeb3b", "#000", "rgba(0, 140, 255, 0.6)")),
+        }
+    }
+}
+
+// User preferences, edited in the Settings panel and kept in localStorage.
+// Missing fields (from an older save) fall back to their defaults.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    font_size: u32,
+    // flipped text for beam-splitter glass; only the prompter, never the camera
+    mirror_prompter: bool,
+    prompter_theme: PrompterTheme,
+    // lines of script visible in the prompter; its height follows from the font size
+    prompter_lines: u32,
+    // flip the on-screen camera preview like a mirror; the recording is never flipped
+    mirror_preview: bool,
+    // BCP 47 tag the recognizer listens for
+    speech_lang: String,
+    // recognized words less certain than this are left out of the live WPM
+    min_confidence: f32,
+    // where Upload sends takes; empty hides the button
+    upload_url: String,
+    // the pace the prompter scrolls at when it isn't following the live WPM
+    target_wpm: u32,
+    // scales whichever pace is in charge, for readers who run ahead of or behind it
+    scroll_speed: f64,
+    // step a whole line at a time, once the pace has got through its words, instead of gliding
+    line_by_line: bool,
+    // slide the current-word highlight from word to word; off jumps it, for less motion
+    animate_highlight: bool,
+    // the prompter holds on the first lines this long after starting, to draw breath
+    lead_in_secs: u32,
+    // container/codec to record with; `None` picks automatically
+    mime_choice: Option<String>,
+    audio_only: bool,
+    // stop recording automatically after this many (unpaused) seconds; `None` is unlimited
+    max_duration_secs: Option<u32>,
+    // for upload limits: finish the take and carry straight on in a new one, numbered
+    // as parts, after this many seconds or megabytes; `None` never splits
+    split_every_secs: Option<u32>,
+    split_at_mb: Option<u32>,
+    // video/audio bitrate asked of the recorder
+    bitrate: BitratePreset,
+    // camera size asked for; changing it restarts the camera
+    resolution: Resolution,
+    frame_rate: FrameRate,
+    // boost applied to the mic before it's metered and recorded; 1 leaves it as is
+    mic_gain: f64,
+    // the camera and mic last picked, by deviceId; used again when they're still plugged in
+    camera_id: Option<String>,
+    mic_id: Option<String>,
+    // which takes are kept in the browser for next time, and whether with their recordings
+    take_storage: TakeStorage,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            font_size: 24,
+            mirror_prompter: false,
+            prompter_theme: PrompterTheme::Standard,
+            prompter_lines: 4,
+            mirror_preview: false,
+            speech_lang: SPEECH_LANGUAGES[0].0.to_string(),
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+            upload_url: String::new(),
+            target_wpm: DEFAULT_WPM,
+            scroll_speed: 1.0,
+            line_by_line: false,
+            animate_highlight: true,
+            lead_in_secs: 2,
+            mime_choice: None,
+            audio_only: false,
+            max_duration_secs: None,
+            split_every_secs: None,
+            split_at_mb: None,
+            bitrate: BitratePreset::Auto,
+            resolution: Resolution::Auto,
+            frame_rate: FrameRate::Auto,
+            mic_gain: 1.0,
+            camera_id: None,
+            mic_id: None,
+            take_storage: TakeStorage::Off,
+        }
+    }
+}
+
+// `None` until settings have been saved (or if they no longer parse)
+fn load_settings() -> Option<Settings> {
+    local_storage()
+        .and_then(|s| s.get_item(SETTINGS_STORAGE_KEY).ok().flatten())
+        .and_then(|json| js_sys::JSON::parse(&json).ok())
+        .and_then(|value| serde_wasm_bindgen::from_value(value).ok())
+}
+
+fn save_settings(settings: &Settings) {
+    let Some(storage) = local_storage() else {
+        return;
+    };
+    let json = serde_wasm_bindgen::to_value(settings)
+        .ok()
+        .and_then(|value| js_sys::JSON::stringify(&value).ok())
+        .and_then(|json| json.as_string());
+    if let Some(json) = json {
+        let _ = storage.set_item(SETTINGS_STORAGE_KEY, &json);
+    }
+}
+
+// A prompter setup to carry to another machine: every setting plus the script.
+// Unknown fields are ignored, and missing settings take their defaults.
 #[derive(Serialize, Deserialize)]
-struct GreetArgs<'a> {
-    name: &'a str,
+struct SessionFile {
+    settings: Settings,
+    script: String,
+}
+
+fn session_json(session: &SessionFile) -> Option<String> {
+    let value = serde_wasm_bindgen::to_value(session).ok()?;
+    js_sys::JSON::stringify_with_replacer_and_space(&value, &JsValue::NULL, &JsValue::from(2))
+        .ok()?
+        .as_string()
+}
+
+fn parse_session(json: &str) -> Result<SessionFile, String> {
+    let value = js_sys::JSON::parse(json).map_err(|_| "that file isn't JSON".to_string())?;
+    serde_wasm_bindgen::from_value(value).map_err(|err| format!("that file isn't a saved session ({err})"))
+}
+
+// Save `url` to disk by clicking a temporary `<a download>`
+fn trigger_download(url: &str, filename: &str) {
+    let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+        return;
+    };
+    if let Ok(anchor) = document.create_element("a") {
+        let anchor: web_sys::HtmlAnchorElement = anchor.unchecked_into();
+        anchor.set_href(url);
+        anchor.set_download(filename);
+        anchor.click();
+    }
+}
+
+// Running inside the Tauri shell rather than a plain browser tab
+fn in_tauri() -> bool {
+    web_sys::window()
+        .map(|w| js_sys::Reflect::has(&w, &"__TAURI__".into()).unwrap_or(false))
+        .unwrap_or(false)
+}
+
+// Call a Tauri command and decode its result; errors come back as display strings
+async fn invoke_command<T: serde::de::DeserializeOwned>(cmd: &str, args: &impl Serialize) -> Result<T, String> {
+    let js_err = |err: JsValue| err.as_string().unwrap_or_else(|| format!("{:?}", err));
+    let args = serde_wasm_bindgen::to_value(args).map_err(|err| err.to_string())?;
+    let value = invoke(cmd, args).await.map_err(js_err)?;
+    serde_wasm_bindgen::from_value(value).map_err(|err| err.to_string())
+}
+
+fn confirm(message: &str) -> bool {
+    web_sys::window()
+        .and_then(|w| w.confirm_with_message(message).ok())
+        .unwrap_or(false)
+}
+
+// Write a take into the user's videos folder through the backend. If the name is
+// taken we ask first (when `ask_before_overwrite`), otherwise pick `name (1).ext`.
+// `Ok(None)` means the user backed out.
+async fn save_recording(
+    blob: web_sys::Blob,
+    filename: String,
+    ask_before_overwrite: bool,
+) -> Result<Option<String>, String> {
+    let mut path: String = invoke_command("default_recording_path", &FilenameArgs { filename: &filename }).await?;
+    if invoke_command::<bool>("file_exists", &PathArgs { path: &path }).await? {
+        let numbered = !ask_before_overwrite
+            || confirm(&format!("{} already exists.\n\nSave as a new numbered copy instead?", path));
+        if numbered {
+            path = invoke_command("next_free_path", &PathArgs { path: &path }).await?;
+        } else if !confirm(&format!("Overwrite {}?", path)) {
+            return Ok(None);
+        }
+    }
+    let buffer = wasm_bindgen_futures::JsFuture::from(blob.array_buffer())
+        .await
+        .map_err(|err| format!("couldn't read the recording: {:?}", err))?;
+    // the bytes go as the raw request body; headers only carry ASCII, hence the encoding
+    let headers = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&headers, &"path".into(), &js_sys::encode_uri_component(&path));
+    let options = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&options, &"headers".into(), &headers);
+    let saved = invoke_raw("save_recording", js_sys::Uint8Array::new(&buffer).into(), options.into())
+        .await
+        .map_err(|err| err.as_string().unwrap_or_else(|| format!("{:?}", err)))?;
+    serde_wasm_bindgen::from_value(saved).map(Some).map_err(|err| err.to_string())
+}
+
+// Read a take the desktop app saved back in, typed as `mime`. The backend answers
+// with the raw bytes, not JSON
+async fn read_saved_take(path: &str, mime: &str) -> Result<web_sys::Blob, String> {
+    let args = serde_wasm_bindgen::to_value(&PathArgs { path }).map_err(|err| err.to_string())?;
+    let bytes = invoke("read_recording", args)
+        .await
+        .map_err(|err| err.as_string().unwrap_or_else(|| format!("{:?}", err)))?;
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime);
+    web_sys::Blob::new_with_buffer_source_sequence_and_options(&js_sys::Array::of1(&bytes), &options)
+        .map_err(|err| js_error_message(&err))
+}
+
+// Outcome of a take's last Save, for the confirmation line beside it
+#[derive(Clone, PartialEq)]
+enum SaveStatus {
+    Saving,
+    Saved(String),
+    Failed(String),
+}
+
+// Log a failed recorder call and put it in front of the user, instead of panicking
+fn report_recorder_error(recorder_error: &UseStateHandle<Option<String>>, action: &str, err: &JsValue) {
+    gloo::console::error!(format!("{} failed:", action), err);
+    recorder_error.set(Some(format!("Couldn't {}: {}", action, js_error_message(err))));
+}
+
+// Where an upload of a take has got to
+#[derive(Clone, PartialEq)]
+enum UploadStatus {
+    // fraction sent, when the browser knows the total
+    Uploading(Option<f64>),
+    Uploaded,
+    // the server answered, but not with a 2xx
+    Rejected(u16, String),
+    // no answer at all: offline, DNS, CORS, …
+    NetworkError,
+}
+
+// POST a take as multipart form data (field "file") to `endpoint`. XHR rather than
+// fetch, because fetch can't report upload progress. `on_status` hears every step.
+fn upload_recording(
+    endpoint: &str,
+    auth_header: Option<&str>,
+    blob: &web_sys::Blob,
+    filename: &str,
+    on_status: Callback<UploadStatus>,
+) -> Result<(), JsValue> {
+    let xhr = web_sys::XmlHttpRequest::new()?;
+    xhr.open_with_async("POST", endpoint, true)?;
+    if let Some(auth) = auth_header {
+        xhr.set_request_header("Authorization", auth)?;
+    }
+    let on_progress = {
+        let on_status = on_status.clone();
+        Closure::wrap(Box::new(move |e: web_sys::ProgressEvent| {
+            let sent = e.length_computable().then(|| e.loaded() / e.total().max(1.0));
+            on_status.emit(UploadStatus::Uploading(sent));
+        }) as Box<dyn FnMut(_)>)
+    };
+    xhr.upload()?.set_onprogress(Some(on_progress.as_ref().unchecked_ref()));
+    on_progress.forget();
+    let on_load = {
+        let on_status = on_status.clone();
+        let xhr = xhr.clone();
+        Closure::wrap(Box::new(move || {
+            let code = xhr.status().unwrap_or(0);
+            on_status.emit(if (200..300).contains(&code) {
+                UploadStatus::Uploaded
+            } else {
+                UploadStatus::Rejected(code, xhr.status_text().unwrap_or_default())
+            });
+        }) as Box<dyn FnMut()>)
+    };
+    xhr.set_onload(Some(on_load.as_ref().unchecked_ref()));
+    on_load.forget();
+    let on_error = {
+        let on_status = on_status.clone();
+        Closure::wrap(Box::new(move || on_status.emit(UploadStatus::NetworkError)) as Box<dyn FnMut()>)
+    };
+    xhr.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+    on_error.forget();
+
+    let form = web_sys::FormData::new()?;
+    form.append_with_blob_and_filename("file", blob, filename)?;
+    on_status.emit(UploadStatus::Uploading(None));
+    xhr.send_with_opt_form_data(Some(&form))
+}
+
+// one pace sample a second; past this many (three hours) the oldest are dropped
+const WPM_SAMPLES_MAX: usize = 3 * 60 * 60;
+
+// The pace over a take as CSV: seconds into the recording, live WPM
+fn pace_csv(samples: &[(u32, u32)]) -> String {
+    let mut csv = String::from("seconds,wpm\n");
+    for (secs, wpm) in samples {
+        csv.push_str(&format!("{secs},{wpm}\n"));
+    }
+    csv
+}
+
+// SVG polyline points drawing `samples` into a `width`×`height` box, slowest at the bottom
+fn sparkline_points(samples: &[(u32, u32)], width: f64, height: f64) -> String {
+    let last_secs = samples.last().map_or(0, |(secs, _)| *secs).max(1) as f64;
+    let top_wpm = samples.iter().map(|(_, wpm)| *wpm).max().unwrap_or(0).max(1) as f64;
+    samples
+        .iter()
+        .map(|(secs, wpm)| {
+            format!("{:.1},{:.1}", *secs as f64 / last_secs * width, height - *wpm as f64 / top_wpm * height)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// One finished recording in the takes list
+#[derive(Clone, PartialEq)]
+struct RecordedTake {
+    // "Take N"; keeps counting up when earlier takes are deleted
+    number: u32,
+    // blob URL for playback/download, revoked when the take is deleted
+    url: String,
+    blob: web_sys::Blob,
+    mime: String,
+    // what `blob` was merged from, kept so a trimmed copy can be merged again
+    chunks: Rc<Vec<(f64, web_sys::Blob)>>,
+    // recorded (unpaused) length
+    duration_secs: f64,
+    // in and out points in whole seconds; chunks only split the take about once a second
+    trim: (u32, u32),
+    save_status: Option<SaveStatus>,
+    upload_status: Option<UploadStatus>,
+    // (seconds into the take, live WPM) once a second while it recorded
+    wpm_samples: Rc<Vec<(u32, u32)>>,
+    // its <video>/<audio>, for the review speed
+    player: NodeRef,
+    // 1–5 stars from review, 0 until rated; kept with the take when the take store keeps it
+    rating: u8,
+    // which part of a recording split by the size/length cap this is, from 1
+    segment: Option<u32>,
+}
+
+impl RecordedTake {
+    // a take kept from an earlier session, put back together from its chunks
+    fn restore(stored: StoredTake, chunks: Vec<(f64, web_sys::Blob)>) -> Result<RecordedTake, JsValue> {
+        let blob = merge_chunks(chunks.iter().map(|(_, blob)| blob), &stored.mime)?;
+        let url = Url::create_object_url_with_blob(&blob)?;
+        Ok(RecordedTake {
+            number: stored.number,
+            url,
+            blob,
+            mime: stored.mime,
+            chunks: Rc::new(chunks),
+            duration_secs: stored.duration_secs,
+            trim: stored.trim,
+            save_status: stored.path.map(SaveStatus::Saved),
+            upload_status: None,
+            wpm_samples: Rc::new(stored.wpm_samples),
+            player: NodeRef::default(),
+            rating: stored.rating,
+            segment: stored.segment,
+        })
+    }
+
+    // everything the take store keeps but the recording
+    fn stored(&self) -> StoredTake {
+        StoredTake {
+            number: self.number,
+            mime: self.mime.clone(),
+            duration_secs: self.duration_secs,
+            trim: self.trim,
+            rating: self.rating,
+            segment: self.segment,
+            wpm_samples: self.wpm_samples.to_vec(),
+            path: match &self.save_status {
+                Some(SaveStatus::Saved(path)) => Some(path.clone()),
+                _ => None,
+            },
+        }
+    }
+
+    fn is_trimmed(&self) -> bool {
+        self.trim != (0, self.duration_secs.ceil() as u32)
+    }
+
+    // what Save/Download hands over: the whole take, or the chunks between the trim points
+    fn export_blob(&self) -> Result<web_sys::Blob, JsValue> {
+        if !self.is_trimmed() {
+            return Ok(self.blob.clone());
+        }
+        let (from, to) = self.trim;
+        trim_chunks(&self.chunks, &self.mime, from as f64 * 1000.0, to as f64 * 1000.0)
+    }
+
+    fn filename(&self) -> String {
+        let filename = recording_filename(&js_sys::Date::new_0(), &self.mime);
+        match self.segment {
+            Some(part) => segment_filename(&filename, part),
+            None => filename,
+        }
+    }
+}
+
+// A recording being split into parts by the size/length cap
+#[derive(Default)]
+struct Segments {
+    // the part recording now; 0 until the first split
+    part: u32,
+    // the stop under way is a split, so recording carries on once its take is in
+    splitting: bool,
+    // recorded length of the parts already finished
+    done_secs: f64,
+}
+
+// past this much held in takes, suggest saving and deleting some; they all live in memory
+const TAKES_WARN_BYTES: f64 = 500.0 * 1024.0 * 1024.0;
+
+// Save the transcript through the same blob → object URL → <a download> path as recordings
+fn download_transcript(captions: &[Caption], format: TranscriptFormat) {
+    download_text(&transcript_file(captions, format), format.mime());
+}
+
+// Put `text` on the clipboard. Fails without the async Clipboard API (insecure
+// contexts have none) or when the browser refuses, e.g. the page isn't focused.
+async fn copy_text(text: &str) -> Result<(), String> {
+    let navigator = web_sys::window().ok_or("no window")?.navigator();
+    let available = js_sys::Reflect::get(&navigator, &"clipboard".into()).is_ok_and(|c| !c.is_undefined());
+    if !available {
+        return Err("the clipboard isn't available here (it needs HTTPS)".to_string());
+    }
+    wasm_bindgen_futures::JsFuture::from(navigator.clipboard().write_text(text))
+        .await
+        .map(|_| ())
+        .map_err(|err| js_error_message(&err))
+}
+
+// how long "Copied!" stays up
+const COPY_NOTICE_MS: u32 = 2000;
+
+// Download generated text as a file of type `mime`, named like a recording
+fn download_text(text: &str, mime: &str) {
+    let parts = js_sys::Array::of1(&text.into());
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime);
+    let Ok(blob) = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options) else {
+        return;
+    };
+    download_blob(&blob, &recording_filename(&js_sys::Date::new_0(), mime));
+}
+
+// Download a blob that has no object URL of its own yet
+fn download_blob(blob: &web_sys::Blob, filename: &str) {
+    if let Ok(url) = Url::create_object_url_with_blob(blob) {
+        trigger_download(&url, filename);
+        // give the download a moment to pick the blob up before letting it go
+        gloo_timers::callback::Timeout::new(1000, move || {
+            let _ = Url::revoke_object_url(&url);
+        })
+        .forget();
+    }
 }
 
 async fn init_recorder(
-    recorder_handle: UseStateHandle<Option<MediaRecorder>>,
+    capture: CaptureConfig,
+    cancelled: Rc<Cell<bool>>,
+    media_error: UseStateHandle<Option<MediaError>>,
+    stream_handle: UseStateHandle<Option<MediaStream>>,
     status: UseStateHandle<RecordingStatus>,
-    chunks: UseStateHandle<Vec<web_sys::Blob>>,
     video_ref: NodeRef,
 ) {
-    let navigator = web_sys::window().unwrap().navigator();
-    let media_devices = navigator.media_devices().unwrap();
+    match request_media_stream(&capture).await {
+        // unmounted or re-initialized while the permission prompt was up
+        Ok(stream) if cancelled.get() => stop_tracks(&stream),
+        Ok(stream) => {
+            // ② Live preview in the <video> element (there's nothing to show without a camera)
+            if let Some(video_el) = video_ref.cast::<web_sys::HtmlVideoElement>() {
+                if capture.audio_only {
+                    video_el.set_src_object(None);
+                } else {
+                    video_el.set_src_object(Some(&stream));
+                    video_el.set_muted(true);
+                    let _ = video_el.play();
+                }
+            }
+
+            // ③ Hand the stream over; the component taps the mic (see `build_mic_graph`)
+            // and builds the MediaRecorder on it (directly, or via the overlay canvas)
+            stream_handle.set(Some(stream));
+            media_error.set(None);
+            status.set(RecordingStatus::Idle);
+        }
+        Err(err) => {
+            gloo::console::error!("getUserMedia error:", &err);
+            if !cancelled.get() {
+                media_error.set(Some(MediaError::from_js(&err)));
+            }
+        }
+    }
+}
+
+// how far the mic can be boosted (×4 is about +12 dB)
+const MIC_GAIN_MAX: f64 = 4.0;
 
-    // ① Request both audio & video
-    let mut constraints = MediaStreamConstraints::new();
-    constraints.video(&JsValue::TRUE);
-    constraints.audio(&JsValue::TRUE);
+// The mic boost, and what it feeds the recorder
+#[derive(Clone, PartialEq)]
+struct MicGain {
+    ctx: AudioContext,
+    gain: web_sys::GainNode,
+    // the camera stream's video with the boosted audio; what takes are recorded from
+    stream: MediaStream,
+}
 
-    let media_promise = media_devices
-        .get_user_media_with_constraints(&constraints)
-        .unwrap();
+// mic → gain → analyser (level meter, voice activity)
+//            → destination (the recording)
+// `None` if Web Audio won't build it; takes then use the raw mic.
+fn build_mic_graph(stream: &MediaStream) -> Option<(AnalyserNode, MicGain)> {
+    let ctx = AudioContext::new().ok()?;
+    let built = (|| {
+        let source = ctx.create_media_stream_source(stream).ok()?;
+        let gain = ctx.create_gain().ok()?;
+        let analyser = ctx.create_analyser().ok()?;
+        let destination = ctx.create_media_stream_destination().ok()?;
+        analyser.set_fft_size(1024);
+        source.connect_with_audio_node(&gain).ok()?;
+        gain.connect_with_audio_node(&analyser).ok()?;
+        gain.connect_with_audio_node(&destination).ok()?;
+        let tracks = js_sys::Array::new();
+        for track in stream.get_video_tracks().iter().chain(destination.stream().get_audio_tracks().iter()) {
+            tracks.push(&track);
+        }
+        let recorded = MediaStream::new_with_tracks(&tracks).ok()?;
+        Some((analyser, MicGain { ctx: ctx.clone(), gain, stream: recorded }))
+    })();
+    if built.is_none() {
+        let _ = ctx.close();
+    }
+    built
+}
 
-    match wasm_bindgen_futures::JsFuture::from(media_promise).await {
-        Ok(js_stream) => {
-            let stream: MediaStream = js_stream.unchecked_into();
+// 75 → "01:15"
+fn format_mm_ss(secs: u32) -> String {
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
 
-            // ② Live preview in the <video> element
-            if let Some(video_el) = video_ref.cast::<web_sys::HtmlVideoElement>() {
-                video_el.set_src_object(Some(&stream));
-                video_el.set_muted(true);
-                let _ = video_el.play();
+// Seconds to read `words` aloud at `wpm`
+fn read_time_secs(words: usize, wpm: u32) -> u32 {
+    if wpm == 0 {
+        return 0;
+    }
+    (words as f64 * 60.0 / wpm as f64).round() as u32
+}
+
+// how often we double-check `status` against `MediaRecorder.state`
+const STATUS_POLL_MS: u32 = 500;
+
+// Case-insensitive subsequence match of `query` in `label`; higher is better.
+// Contiguous runs and matches near the start score higher.
+fn fuzzy_score(query: &str, label: &str) -> Option<i32> {
+    let label: Vec<char> = label.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev_hit: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let hit = pos + label[pos..].iter().position(|&c| c == q)?;
+        score += match prev_hit {
+            Some(p) if p + 1 == hit => 5,
+            _ => 1,
+        };
+        if hit == 0 || label[hit - 1] == ' ' {
+            score += 3;
+        }
+        prev_hit = Some(hit);
+        pos = hit + 1;
+    }
+    Some(score - label.len() as i32 / 10)
+}
+
+// One entry in the quick-settings palette
+#[derive(Clone, PartialEq)]
+struct PaletteCommand {
+    label: &'static str,
+    action: Callback<()>,
+}
+
+#[derive(Properties, PartialEq)]
+struct CommandPaletteProps {
+    commands: Vec<PaletteCommand>,
+    on_close: Callback<()>,
+}
+
+#[function_component(CommandPalette)]
+fn command_palette(props: &CommandPaletteProps) -> Html {
+    let query = use_state(String::new);
+    let selected = use_state(|| 0usize);
+    let input_ref = use_node_ref();
+    {
+        let input_ref = input_ref.clone();
+        use_effect_with((), move |_| {
+            if let Some(input) = input_ref.cast::<HtmlElement>() {
+                let _ = input.focus();
             }
+            || ()
+        });
+    }
 
-            // ③ Create MediaRecorder on that same stream
-            let recorder = MediaRecorder::new_with_media_stream(&stream).unwrap();
+    let mut matches: Vec<(i32, &PaletteCommand)> = props
+        .commands
+        .iter()
+        .filter_map(|c| fuzzy_score(&query, c.label).map(|score| (score, c)))
+        .collect();
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    let matches: Vec<PaletteCommand> = matches.into_iter().map(|(_, c)| c.clone()).collect();
+    let current = (*selected).min(matches.len().saturating_sub(1));
 
-            // ondataavailable → collect blobs
-            {
-                let chunks_clone = chunks.clone();
-                let on_data = Closure::wrap(Box::new(move |e: web_sys::BlobEvent| {
-                    // e.data() is Option<web_sys::Blob>, so just unwrap it
-                    if let Some(blob) = e.data() {
-                        let mut current = (*chunks_clone).clone();
-                        current.push(blob);
-                        chunks_clone.set(current);
+    let run = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |cmd: PaletteCommand| {
+            on_close.emit(());
+            cmd.action.emit(());
+        })
+    };
+    let onkeydown = {
+        let matches = matches.clone();
+        let selected = selected.clone();
+        let on_close = props.on_close.clone();
+        let run = run.clone();
+        Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+            "Escape" => on_close.emit(()),
+            "ArrowDown" => {
+                e.prevent_default();
+                selected.set((current + 1).min(matches.len().saturating_sub(1)));
+            }
+            "ArrowUp" => {
+                e.prevent_default();
+                selected.set(current.saturating_sub(1));
+            }
+            "Enter" => {
+                if let Some(cmd) = matches.get(current) {
+                    run.emit(cmd.clone());
+                }
+            }
+            _ => {}
+        })
+    };
+
+    html! {
+        <div class="palette-backdrop" onclick={props.on_close.reform(|_| ())}>
+            <div class="palette" onclick={Callback::from(|e: MouseEvent| e.stop_propagation())}>
+                <input
+                    ref={input_ref}
+                    type="text"
+                    placeholder="Type a command…"
+                    value={(*query).clone()}
+                    {onkeydown}
+                    oninput={Callback::from({
+                        let query = query.clone();
+                        let selected = selected.clone();
+                        move |e: InputEvent| {
+                            query.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value());
+                            selected.set(0);
+                        }
+                    })}
+                />
+                <ul>
+                    { for matches.iter().enumerate().map(|(i, cmd)| {
+                        let onclick = {
+                            let run = run.clone();
+                            let cmd = cmd.clone();
+                            Callback::from(move |_| run.emit(cmd.clone()))
+                        };
+                        html! {
+                            <li class={classes!((i == current).then_some("selected"))} {onclick}>{ cmd.label }</li>
+                        }
+                    }) }
+                </ul>
+            </div>
+        </div>
+    }
+}
+
+// "Follow my voice": each recognized word may match one of the next few script
+// words, so skipped or fumbled words don't stall the prompter
+const FOLLOW_LOOKAHEAD: usize = 8;
+// fraction of the remaining distance the prompter closes every FRAME_FALLBACK_MS, so it glides
+const FOLLOW_EASING: f64 = 0.2;
+
+// tick period where there's no requestAnimationFrame
+const FRAME_FALLBACK_MS: u32 = 50;
+// a longer gap (a background tab, a stall) is treated as this, so the prompter doesn't leap
+const FRAME_MAX_GAP_MS: f64 = 100.0;
+
+// the frame callback's own slot: it re-requests itself, and is emptied to end the loop
+type FrameSlot = Rc<RefCell<Option<Closure<dyn FnMut(f64)>>>>;
+
+// Runs `tick` with the milliseconds since its last run: every display frame via
+// requestAnimationFrame, or every FRAME_FALLBACK_MS where that's missing. Stops when dropped.
+enum FrameLoop {
+    Frames { id: Rc<Cell<i32>>, callback: FrameSlot },
+    // held only so it keeps ticking
+    Interval { _interval: Interval },
+}
+
+impl FrameLoop {
+    fn start(mut tick: impl FnMut(f64) + 'static) -> FrameLoop {
+        let window = web_sys::window().expect("no window");
+        if !js_sys::Reflect::has(&window, &JsValue::from_str("requestAnimationFrame")).unwrap_or(false) {
+            let mut last = js_sys::Date::now();
+            let _interval = Interval::new(FRAME_FALLBACK_MS, move || {
+                let now = js_sys::Date::now();
+                tick((now - last).min(FRAME_MAX_GAP_MS));
+                last = now;
+            });
+            return FrameLoop::Interval { _interval };
+        }
+        let id = Rc::new(Cell::new(0));
+        let callback: FrameSlot = Rc::new(RefCell::new(None));
+        let frame = {
+            let id = id.clone();
+            let callback = callback.clone();
+            let window = window.clone();
+            // the first frame only sets where timing starts from
+            let mut last = None::<f64>;
+            Closure::wrap(Box::new(move |now: f64| {
+                tick(last.map_or(0.0, |last| (now - last).min(FRAME_MAX_GAP_MS)));
+                last = Some(now);
+                if let Some(frame) = callback.borrow().as_ref() {
+                    id.set(window.request_animation_frame(frame.as_ref().unchecked_ref()).unwrap_or(0));
+                }
+            }) as Box<dyn FnMut(f64)>)
+        };
+        id.set(window.request_animation_frame(frame.as_ref().unchecked_ref()).unwrap_or(0));
+        *callback.borrow_mut() = Some(frame);
+        FrameLoop::Frames { id, callback }
+    }
+}
+
+impl Drop for FrameLoop {
+    fn drop(&mut self) {
+        if let FrameLoop::Frames { id, callback } = self {
+            if let Some(window) = web_sys::window() {
+                let _ = window.cancel_animation_frame(id.get());
+            }
+            callback.borrow_mut().take();
+        }
+    }
+}
+
+// lowercase letters and digits only, so "Hello," matches "hello"
+fn normalize_word(word: &str) -> String {
+    word.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+// How many script words the speaker has got through. Spoken words that match
+// nothing nearby are ignored rather than moving the position.
+fn script_progress(script: &[String], spoken: &[String]) -> usize {
+    let mut pos = 0;
+    for word in spoken.iter().filter(|w| !w.is_empty()) {
+        if let Some(offset) = script[pos..].iter().take(FOLLOW_LOOKAHEAD).position(|w| w == word) {
+            pos += offset + 1;
+        }
+    }
+    pos
+}
+
+// What speech recognition has heard: settled text, plus the guess still being revised
+#[derive(Clone, PartialEq, Default)]
+struct Transcript {
+    final_text: String,
+    interim: String,
+}
+
+// One finalized stretch of speech, timed from when recognition started
+#[derive(Clone)]
+struct Caption {
+    start_ms: f64,
+    end_ms: f64,
+    text: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TranscriptFormat {
+    Text,
+    Srt,
+}
+
+impl TranscriptFormat {
+    fn mime(self) -> &'static str {
+        match self {
+            TranscriptFormat::Text => "text/plain",
+            TranscriptFormat::Srt => "application/x-subrip",
+        }
+    }
+}
+
+// SRT timing, `HH:MM:SS,mmm`, to the nearest millisecond
+fn format_srt_time(ms: f64) -> String {
+    let ms = ms.max(0.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+fn transcript_file(captions: &[Caption], format: TranscriptFormat) -> String {
+    match format {
+        TranscriptFormat::Text => captions.iter().map(|c| c.text.as_str()).collect::<Vec<_>>().join("\n") + "\n",
+        TranscriptFormat::Srt => captions
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                format!(
+                    "{}\n{} --> {}\n{}\n\n",
+                    i + 1,
+                    format_srt_time(c.start_ms),
+                    format_srt_time(c.end_ms),
+                    c.text
+                )
+            })
+            .collect(),
+    }
+}
+
+// only the end of a long transcript is worth showing
+const TRANSCRIPT_TAIL_CHARS: usize = 240;
+
+// the last `max_chars` characters of `text`, starting on a word boundary when it's cut
+fn text_tail(text: &str, max_chars: usize) -> &str {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text;
+    }
+    let start = text.char_indices().nth(count - max_chars).map_or(0, |(i, _)| i);
+    let tail = &text[start..];
+    tail.find(' ').map_or(tail, |i| &tail[i + 1..])
+}
+
+// quiet this long while recording and we suggest checking the mic
+const NO_SPEECH_HINT_MS: f64 = 8000.0;
+
+// bounds of the pace slider and box, in words per minute
+const MANUAL_WPM_RANGE: (u32, u32) = (40, 300);
+// bounds of the scroll speed multiplier
+const SCROLL_SPEED_RANGE: (f64, f64) = (0.5, 2.0);
+
+// pace before anything has been measured or chosen
+const DEFAULT_WPM: u32 = 120;
+
+// A finished take, as handed to whatever embeds the App
+#[derive(Clone, PartialEq, Debug)]
+pub struct RecordingResult {
+    // owned by the App: revoked when the take is deleted, reset, or the App unmounts
+    pub blob_url: String,
+    pub duration_secs: f64,
+    pub mime_type: String,
+}
+
+// Starting values for an embedded App (whatever the user has saved still wins),
+// and where to tell the host about each finished take
+#[derive(Properties, PartialEq)]
+pub struct AppProps {
+    #[prop_or(DEFAULT_WPM)]
+    pub default_wpm: u32,
+    #[prop_or_default]
+    pub default_script: Option<String>,
+    #[prop_or_default]
+    pub on_recording: Callback<RecordingResult>,
+    // start as a bare teleprompter, without asking for the camera or mic
+    #[prop_or_default]
+    pub prompter_only: bool,
+}
+
+impl Default for AppProps {
+    fn default() -> Self {
+        AppProps { default_wpm: DEFAULT_WPM, default_script: None, on_recording: Callback::noop(), prompter_only: false }
+    }
+}
+
+#[function_component(App)]
+pub fn app(props: &AppProps) -> Html {
+    // Live WPM using the browser speech to text API
+    let live_wpm = use_state(|| props.default_wpm);
+    // read by the once-a-second pace sampler while recording
+    let live_wpm_seen = use_mut_ref(|| props.default_wpm);
+    *live_wpm_seen.borrow_mut() = *live_wpm;
+    // the current take's pace samples, handed to the take when it's finished
+    let wpm_samples = use_mut_ref(Vec::<(u32, u32)>::new);
+    // WPM is measured against this, which the recorder's pauses stop
+    let active_clock = use_mut_ref(ActiveClock::default);
+    let active_clock_recog = active_clock.clone();
+    // font size, mirroring, language, pace, scroll speed and lead-in, format, quality and audio-only, saved on every change
+    let settings = use_state(|| {
+        load_settings().unwrap_or_else(|| Settings { target_wpm: props.default_wpm, ..Settings::default() })
+    });
+    use_effect_with((*settings).clone(), |settings| {
+        save_settings(settings);
+        || ()
+    });
+    let auto_pace = use_state(|| false);
+    // false when the browser has no SpeechRecognition (e.g. Firefox); pace is then manual only
+    let speech_supported = use_state(|| true);
+    // read by the scroll interval on every tick, so pace changes don't restart the scroll
+    let scroll_wpm = use_mut_ref(|| props.default_wpm);
+    *scroll_wpm.borrow_mut() = if *auto_pace && *speech_supported { *live_wpm } else { settings.target_wpm };
+    // likewise for the multiplier on top of it
+    let scroll_speed = use_mut_ref(|| 1.0f64);
+    *scroll_speed.borrow_mut() = settings.scroll_speed;
+    // and for gliding versus stepping by lines
+    let line_by_line = use_mut_ref(|| false);
+    *line_by_line.borrow_mut() = settings.line_by_line;
+    let recog_ref = use_mut_ref(|| None::<Recognizer>);
+        // on-mount: start recognition, and restart it whenever the language changes
+    let wpm_recog = live_wpm.clone();
+    // what the recognizer has heard, shown under the WPM
+    let transcript = use_state(Transcript::default);
+    let transcript_recog = transcript.clone();
+    // finalized speech with timings, for the transcript download
+    let captions = use_mut_ref(Vec::<Caption>::new);
+    let captions_recog = captions.clone();
+    let transcript_format = use_state(|| TranscriptFormat::Srt);
+    // how the last Copy went; cleared after COPY_NOTICE_MS
+    let copy_notice = use_state(|| None::<Result<(), String>>);
+    let copy_notice_timer = use_mut_ref(|| None::<gloo_timers::callback::Timeout>);
+    // when the recognizer last reported anything, and whether it's been quiet too long
+    // while recording; recognition tends to die silently rather than with an error
+    let last_result_at = use_mut_ref(js_sys::Date::now);
+    let no_speech = use_state(|| false);
+    let last_result_recog = last_result_at.clone();
+    let no_speech_recog = no_speech.clone();
+    let speech_supported_set = speech_supported.clone();
+    // read on every result, so changing it doesn't restart recognition
+    let min_confidence = use_mut_ref(|| DEFAULT_MIN_CONFIDENCE);
+    *min_confidence.borrow_mut() = settings.min_confidence;
+    let min_confidence_recog = min_confidence.clone();
+    // bumped by Reset to start recognition (and the transcript) over
+    let speech_resets = use_state(|| 0u32);
+    // changing the language restarts recognition
+    use_effect_with((settings.speech_lang.clone(), *speech_resets), move |(lang, _)| {
+        let mut sampler = None;
+        match Recognizer::new(lang) {
+            Err(err) => {
+                web_sys::console::error_1(&format!("SpeechRecognition::new() failed: {:?}", err).into());
+                speech_supported_set.set(false);
+            }
+            Ok(recognizer) => {
+                let recog = recognizer.recog.clone();
+                // stash it in our ref so we can stop it later
+                recog_ref.borrow_mut().replace(recognizer);
+    
+                // time markers
+                let start_time = js_sys::Date::now();
+                let tracker = Rc::new(RefCell::new(WpmTracker::new(active_clock_recog.borrow().now(start_time))));
+                {
+                    let tracker = tracker.clone();
+                    let wpm_recog = wpm_recog.clone();
+                    let active_clock = active_clock_recog.clone();
+                    // re-evaluate every second so the number also falls when speech stops
+                    sampler = Some(Interval::new(1000, move || {
+                        let now = active_clock.borrow().now(js_sys::Date::now());
+                        if let Some(current_wpm) = tracker.borrow_mut().current_wpm(now) {
+                            wpm_recog.set(current_wpm.round() as u32);
+                        }
+                    }));
+                }
+    
+                // onresult handler
+                let transcript_recog = transcript_recog.clone();
+                let captions = captions_recog.clone();
+                captions.borrow_mut().clear();
+                let active_clock = active_clock_recog.clone();
+                let recog_result = recog_ref.clone();
+                let this = recog.clone();
+                // when the utterance being recognized right now was first heard
+                let mut utterance_started = None::<f64>;
+                // finalized text from every session so far; results before `resultIndex` never change
+                let mut final_text = String::new();
+                let last_result_at = last_result_recog.clone();
+                let no_speech = no_speech_recog.clone();
+                let min_confidence = min_confidence_recog.clone();
+                let on_result = Closure::wrap(Box::new(move |e: SpeechRecognitionEvent| {
+                    *last_result_at.borrow_mut() = js_sys::Date::now();
+                    no_speech.set(false);
+                    let mut interim = String::new();
+                    // (transcript, confidence, is_final) for each result that may have changed
+                    let mut fresh = Vec::new();
+                    let now = js_sys::Date::now();
+                    let heard_at = *utterance_started.get_or_insert(now);
+                    let results = e.results() .expect("SpeechRecognitionEvent should always have results");
+                    // results before resultIndex were final last time and can't change
+                    for i in e.result_index()..results.length() {
+                        let Some(res) = results.get(i) else { continue };
+                        let Some(best) = res.get(0) else { continue };
+                        let text = best.transcript();
+                        let part = if res.is_final() { &mut final_text } else { &mut interim };
+                        part.push_str(text.trim());
+                        part.push(' ');
+                        if res.is_final() && !text.trim().is_empty() {
+                            captions.borrow_mut().push(Caption {
+                                start_ms: heard_at - start_time,
+                                end_ms: now - start_time,
+                                text: text.trim().to_string(),
+                            });
+                        }
+                        fresh.push((text, best.confidence(), res.is_final()));
+                    }
+                    // the next utterance starts timing from its first result
+                    if interim.is_empty() {
+                        utterance_started = None;
+                    }
+                    transcript_recog.set(Transcript { final_text: final_text.clone(), interim });
+
+                    let active_now = active_clock.borrow().now(now);
+                    if let Some(r) = recog_result.borrow_mut().as_mut().filter(|r| r.recog == this) {
+                        r.heard(
+                            e.result_index() as usize,
+                            fresh.iter().map(|(text, confidence, is_final)| (text.as_str(), *confidence, *is_final)),
+                            *min_confidence.borrow(),
+                            &mut tracker.borrow_mut(),
+                            active_now,
+                        );
+                    }
+                    if let Some(current_wpm) = tracker.borrow_mut().current_wpm(active_now) {
+                        wpm_recog.set(current_wpm.round() as u32);
                     }
                 }) as Box<dyn FnMut(_)>);
-                recorder.set_ondataavailable(Some(on_data.as_ref().unchecked_ref()));
-                on_data.forget();
+    
+                recog.set_onresult(Some(on_result.as_ref().unchecked_ref()));
+                on_result.forget();
+
+                // onend → browsers end recognition after silence or a timeout; start it again
+                // unless we stopped it on purpose, giving up if it keeps ending straight away
+                let session_started = Rc::new(Cell::new(js_sys::Date::now()));
+                let on_end = {
+                    let recog_ref = recog_ref.clone();
+                    let session_started = session_started.clone();
+                    let this = recog.clone();
+                    Closure::wrap(Box::new(move || {
+                        let mut guard = recog_ref.borrow_mut();
+                        // after a language switch the ref already holds the replacement recognizer
+                        let Some(r) = guard.as_mut().filter(|r| r.recog == this) else {
+                            return;
+                        };
+                        r.started = false;
+                        if !r.keep_alive {
+                            return;
+                        }
+                        let now = js_sys::Date::now();
+                        r.quick_ends = if now - session_started.get() < SPEECH_QUICK_END_MS { r.quick_ends + 1 } else { 0 };
+                        if r.quick_ends >= SPEECH_MAX_QUICK_ENDS {
+                            gloo::console::warn!("speech recognition keeps ending immediately; not restarting");
+                            r.keep_alive = false;
+                            return;
+                        }
+                        // a fresh session numbers its results from zero again
+                        r.words = SessionWords::default();
+                        session_started.set(now);
+                        if let Err(err) = r.start() {
+                            gloo::console::error!("speech recognition restart failed:", js_error_message(&err));
+                            r.keep_alive = false;
+                        }
+                    }) as Box<dyn FnMut()>)
+                };
+                recog.set_onend(Some(on_end.as_ref().unchecked_ref()));
+                on_end.forget();
+
+                // onstart → the session really is running, however it was started
+                let on_start = {
+                    let recog_ref = recog_ref.clone();
+                    let this = recog.clone();
+                    Closure::wrap(Box::new(move || {
+                        if let Some(r) = recog_ref.borrow_mut().as_mut().filter(|r| r.recog == this) {
+                            r.started = true;
+                        }
+                    }) as Box<dyn FnMut()>)
+                };
+                recog.set_onstart(Some(on_start.as_ref().unchecked_ref()));
+                on_start.forget();
+
+                // start recognition
+                if let Some(r) = recog_ref.borrow_mut().as_mut() {
+                    if let Err(err) = r.start() {
+                        gloo::console::error!("speech recognition failed to start:", js_error_message(&err));
+                    }
+                }
+            }
+        }
+    
+        // **Remember**: only two arguments to use_effect_with,
+        // so we return our teardown from inside this one closure:
+        move || {
+            drop(sampler);
+            // taking it out of the ref is what tells onend not to restart
+            let taken = recog_ref.borrow_mut().take();
+            if let Some(r) = taken {
+                r.recog.stop();
+            }
+        }
+    });
+    // refs & state
+    let video_ref = use_node_ref();
+    let overlay_canvas_ref = use_node_ref();
+    // every take this session, oldest first
+    let takes = use_state(Vec::<RecordedTake>::new);
+    // the list as of the last render, for callbacks that outlive it (on_take, saves)
+    let takes_seen = use_mut_ref(Vec::<RecordedTake>::new);
+    *takes_seen.borrow_mut() = (*takes).clone();
+    let take_count = use_mut_ref(|| 0u32);
+    // the host's completion callback as of the last render, for `on_take`
+    let on_recording = use_mut_ref(Callback::<RecordingResult>::noop);
+    *on_recording.borrow_mut() = props.on_recording.clone();
+    // set by Reset so the take its stop produces is thrown away
+    let discard_take = use_mut_ref(|| false);
+    let segments = use_mut_ref(Segments::default);
+    // Record pressed before getUserMedia finished: start as soon as the recorder exists.
+    // Also how recording resumes after a split
+    let start_pending = use_state(|| false);
+    // in the desktop app takes are saved to disk instead of downloaded
+    let ask_before_overwrite = use_state(|| true);
+    // how the last session import went, for the line under the buttons
+    let session_notice = use_state(|| None::<Result<String, String>>);
+    // the take last focused in the list, which the 1–5 keys rate
+    let selected_take = use_state(|| None::<u32>);
+    // review the best first, and/or hide takes rated below this (0 shows all)
+    let best_first = use_state(|| false);
+    let min_rating = use_state(|| 0u8);
+    // review speed for every take's player, new ones included
+    let playback_rate = use_state(|| 1.0f64);
+    {
+        let players: Vec<NodeRef> = takes.iter().map(|t| t.player.clone()).collect();
+        use_effect_with((playback_rate.to_bits(), players), |(rate, players)| {
+            let rate = f64::from_bits(*rate);
+            for player in players.iter().filter_map(|p| p.cast::<web_sys::HtmlMediaElement>()) {
+                // the default survives the element (re)loading its source
+                player.set_default_playback_rate(rate);
+                player.set_playback_rate(rate);
+            }
+            || ()
+        });
+    }
+    // sent as the Authorization header on uploads; kept out of the saved settings,
+    // since localStorage is no place for a credential
+    let upload_auth = use_state(String::new);
+    // a blob URL keeps its blob alive until revoked: deleting a take drops its
+    // URL, and whatever is left goes on unmount
+    {
+        let takes_seen = takes_seen.clone();
+        use_effect_with((), move |_| {
+            move || {
+                for take in takes_seen.borrow().iter() {
+                    let _ = Url::revoke_object_url(&take.url);
+                }
             }
+        });
+    }
+    let update_take = {
+        let takes = takes.clone();
+        let takes_seen = takes_seen.clone();
+        move |number: u32, edit: &dyn Fn(&mut RecordedTake)| {
+            let mut list = takes_seen.borrow().clone();
+            if let Some(take) = list.iter_mut().find(|t| t.number == number) {
+                edit(take);
+                takes.set(list);
+            }
+        }
+    };
+    let delete_take = {
+        let takes = takes.clone();
+        let takes_seen = takes_seen.clone();
+        Callback::from(move |number: u32| {
+            let mut list = takes_seen.borrow().clone();
+            list.retain(|t| {
+                if t.number == number {
+                    let _ = Url::revoke_object_url(&t.url);
+                }
+                t.number != number
+            });
+            takes.set(list);
+        })
+    };
+    // Takes kept across reloads (see `take_store`): whatever the browser holds is read
+    // back once on mount, and from then on brought in line with the list and the
+    // setting whenever either changes
+    let take_db = use_state(|| None::<web_sys::IdbDatabase>);
+    let takes_stored = use_mut_ref(Stored::default);
+    // why keeping takes last failed, and (used, allowed) bytes as of the last write
+    let take_store_error = use_state(|| None::<String>);
+    let storage_estimate = use_state(|| None::<(f64, f64)>);
+    {
+        let take_db = take_db.clone();
+        let takes_stored = takes_stored.clone();
+        let takes = takes.clone();
+        let takes_seen = takes_seen.clone();
+        let take_count = take_count.clone();
+        let take_store_error = take_store_error.clone();
+        let storage_estimate = storage_estimate.clone();
+        let strategy = settings.take_storage;
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                let opened = match take_store::open().await {
+                    Ok(db) => take_store::load(&db).await.map(|loaded| (db, loaded)),
+                    Err(err) => Err(err),
+                };
+                let (db, loaded) = match opened {
+                    Ok(opened) => opened,
+                    Err(err) => {
+                        gloo::console::warn!("couldn't open the take store:", &err);
+                        take_store_error.set(Some(js_error_message(&err)));
+                        return;
+                    }
+                };
+                {
+                    let mut stored = takes_stored.borrow_mut();
+                    for (take, chunks) in &loaded {
+                        stored.details.insert(take.number, take.clone());
+                        if chunks.is_some() {
+                            stored.media.insert(take.number);
+                        }
+                    }
+                }
+                // whatever isn't restored here is dropped from the store by the next sync
+                let mut restored = Vec::new();
+                for (take, chunks) in loaded {
+                    if strategy.keeps(take.path.as_deref()) == Kept::Nothing {
+                        continue;
+                    }
+                    let chunks = match (chunks, &take.path) {
+                        (Some(chunks), _) => chunks,
+                        // only the path was kept: read the file back in, as one chunk
+                        (None, Some(path)) if in_tauri() => match read_saved_take(path, &take.mime).await {
+                            Ok(blob) => vec![(take.duration_secs * 1000.0, blob)],
+                            Err(err) => {
+                                gloo::console::warn!(format!("couldn't reopen take {} from {}: {}", take.number, path, err));
+                                continue;
+                            }
+                        },
+                        _ => continue,
+                    };
+                    match RecordedTake::restore(take, chunks) {
+                        Ok(take) => restored.push(take),
+                        Err(err) => gloo::console::warn!("couldn't restore a take:", err),
+                    }
+                }
+                if let Some(last) = restored.last() {
+                    let mut count = take_count.borrow_mut();
+                    *count = (*count).max(last.number);
+                }
+                restored.extend(takes_seen.borrow().iter().cloned());
+                takes.set(restored);
+                take_db.set(Some(db));
+                storage_estimate.set(take_store::estimate().await);
+            });
+            || ()
+        });
+    }
+    {
+        let takes_stored = takes_stored.clone();
+        let take_store_error = take_store_error.clone();
+        let storage_estimate = storage_estimate.clone();
+        use_effect_with(
+            ((*takes).clone(), settings.take_storage, (*take_db).clone()),
+            move |(takes, strategy, db)| {
+                let wanted: Vec<(StoredTake, Kept)> = takes
+                    .iter()
+                    .map(|take| {
+                        let stored = take.stored();
+                        let kept = strategy.keeps(stored.path.as_deref());
+                        (stored, kept)
+                    })
+                    .collect();
+                let plan = plan_sync(&takes_stored.borrow(), &wanted);
+                if let (Some(db), false) = (db.clone(), plan.is_empty()) {
+                    let chunks: HashMap<u32, Rc<Vec<(f64, web_sys::Blob)>>> =
+                        takes.iter().map(|take| (take.number, take.chunks.clone())).collect();
+                    spawn_local(async move {
+                        match take_store::write(&db, &plan, |number| chunks.get(&number).map(|c| c.to_vec())).await {
+                            Ok(()) => {
+                                takes_stored.borrow_mut().apply(plan);
+                                take_store_error.set(None);
+                            }
+                            Err(err) => {
+                                gloo::console::error!("couldn't keep the takes:", &err);
+                                take_store_error.set(Some(js_error_message(&err)));
+                            }
+                        }
+                        storage_estimate.set(take_store::estimate().await);
+                    });
+                }
+                || ()
+            },
+        );
+    }
+    let stream_handle = use_state(|| None::<MediaStream>);
+    // rehearsing: no getUserMedia at all, just the script and the prompter. Leaving
+    // this mode is what first asks for the camera
+    let prompter_only = use_state(|| props.prompter_only);
+    // chosen camera/mic, and what's available to choose from
+    let capture = use_state(CaptureConfig::default);
+    let cameras = use_state(Vec::<InputDevice>::new);
+    let mics = use_state(Vec::<InputDevice>::new);
+    // pre-flight: whether the lists above are real yet, and what the Permissions API says
+    let devices_listed = use_state(|| false);
+    let camera_access = use_state(|| Access::Unknown);
+    let mic_access = use_state(|| Access::Unknown);
+    // why the camera/mic isn't available; bumping `media_retries` re-runs init
+    let media_error = use_state(|| None::<MediaError>);
+    let media_retries = use_state(|| 0u32);
+    let recorder_handle = use_state(|| None::<Recorder>);
+    // set when a camera change was applied to the running stream, so init leaves it be
+    let camera_swapped_live = use_mut_ref(|| false);
+    // the last recorder call that failed, until dismissed
+    let recorder_error = use_state(|| None::<String>);
+    // set when a camera change had to end the take and start another, until dismissed
+    let take_split_notice = use_state(|| false);
+    let analyser_handle = use_state(|| None::<AnalyserNode>);
+    // the boost between the mic and the recorder
+    let mic_gain = use_state(|| None::<MicGain>);
+    let status = use_state(|| RecordingStatus::Idle);
+    // latest rendered status, for callbacks that outlive this render
+    let status_seen = use_mut_ref(|| RecordingStatus::Idle);
+    *status_seen.borrow_mut() = (*status).clone();
 
-            // onstop → update status
-            {
-                let status_clone = status.clone();
-                let on_stop = Closure::wrap(Box::new(move || {
-                    status_clone.set(RecordingStatus::Idle);
-                }) as Box<dyn FnMut()>);
-                recorder.set_onstop(Some(on_stop.as_ref().unchecked_ref()));
-                on_stop.forget();
+    // lower-third overlay; the compositor reads the live config through a ref
+    let lower_third = use_state(LowerThird::default);
+    let lower_third_cfg = use_mut_ref(LowerThird::default);
+    *lower_third_cfg.borrow_mut() = (*lower_third).clone();
+    let rec_started = use_mut_ref(|| None::<f64>);
+    let stamp_mode = use_state(|| StampMode::None);
+    let stamp_cfg = use_mut_ref(|| StampMode::None);
+    *stamp_cfg.borrow_mut() = *stamp_mode;
+    // any burned-in overlay means recording from the canvas instead of the camera
+    let audio_only = settings.audio_only;
+    // the devices picked here, plus whether to ask for a camera at all, and at what size
+    let capture_cfg = CaptureConfig {
+        audio_only,
+        resolution: settings.resolution,
+        frame_rate: settings.frame_rate,
+        ..(*capture).clone()
+    };
+    let overlay_active = !audio_only && (lower_third.enabled || *stamp_mode != StampMode::None);
+
+    // break reminder for long sessions, independent of any recording
+    let break_reminder_on = use_state(|| false);
+    let break_interval_mins = use_state(|| 25u32);
+    let break_due = use_state(|| false);
+    {
+        let break_due = break_due.clone();
+        use_effect_with((*break_reminder_on, *break_interval_mins), move |(on, mins)| {
+            let ticker = on.then(|| {
+                let limit = *mins * 60;
+                let mut secs = 0u32;
+                Interval::new(1000, move || {
+                    secs += 1;
+                    if secs >= limit {
+                        secs = 0;
+                        break_due.set(true);
+                    }
+                })
+            });
+            move || drop(ticker)
+        });
+    }
+
+    // hint the browser towards a hardware encoder via the codec choice
+    let prefer_hw_encoding = use_state(|| true);
+    let encoder_report = use_state(|| EncoderReport::Unknown);
+
+    // what this browser can record, probed once on mount
+    let formats = use_memo((), |_| FormatSupport::probe());
+    let supported_types = formats.recordable(audio_only);
+    let recording_type = {
+        let formats = formats.clone();
+        use_memo(
+            (settings.mime_choice.clone(), *prefer_hw_encoding, audio_only),
+            move |(choice, prefer_hw, audio_only)| {
+                resolve_recording_type(choice.as_deref(), *prefer_hw, *audio_only, |t| formats.supports(t))
+            },
+        )
+    };
+
+    let script = use_state(|| load_script().or_else(|| props.default_script.clone()).unwrap_or_default());
+    // read by the scroll interval, so editing the script doesn't restart (and rewind) the prompter
+    let script_text = use_mut_ref(String::new);
+    script_text.borrow_mut().clone_from(&script);
+    // pending debounced write of the script to localStorage
+    let script_save = use_mut_ref(|| None::<gloo_timers::callback::Timeout>);
+    let is_prompting = use_state(|| false);
+    // bumped to send a running prompter back to the first word (e.g. on retake)
+    let prompter_resets = use_state(|| 0u32);
+    // scroll to where the transcript says the speaker is, instead of by WPM
+    let follow_voice = use_state(|| false);
+    let following = *follow_voice && *speech_supported;
+    // everything heard so far, read by the scroll interval
+    let heard = use_mut_ref(String::new);
+    *heard.borrow_mut() = format!("{} {}", transcript.final_text, transcript.interim);
+    // only advance the prompter while the mic hears a voice
+    let vad_scroll = use_state(|| false);
+    let vad_sensitivity = use_state(|| 50u32);
+    let tele_ref = use_node_ref();
+    // how far through the script the prompter is; written by the scroll loop, not rendered
+    let progress_bar_ref = use_node_ref();
+    // index of the word the reader should be on
+    let current_word = use_state(|| 0usize);
+    // bumped when the window is resized: the stage takes the page's width, so the
+    // prompter rewraps and anything placed from the layout must be measured again
+    let layout_rev = use_state(|| 0u32);
+    {
+        let layout_rev = layout_rev.clone();
+        use_effect_with((), move |_| {
+            let window = web_sys::window().expect("no window");
+            let rev = Rc::new(Cell::new(0u32));
+            let listener = gloo::events::EventListener::new(&window, "resize", move |_| {
+                rev.set(rev.get() + 1);
+                layout_rev.set(rev.get());
+            });
+            move || drop(listener)
+        });
+    }
+    // the sliding highlight behind the current word, placed straight from the word's span
+    let highlight_ref = use_node_ref();
+    {
+        let tele_ref = tele_ref.clone();
+        let highlight_ref = highlight_ref.clone();
+        use_effect_with(
+            (*current_word, *is_prompting, settings.animate_highlight, *layout_rev),
+            move |(word, prompting, animate, _)| {
+                if let Some(highlight) = highlight_ref.cast::<HtmlElement>() {
+                    let span = tele_ref
+                        .cast::<HtmlElement>()
+                        .and_then(|tele| tele.get_elements_by_tag_name("span").item(*word as u32))
+                        .and_then(|span| span.dyn_into::<HtmlElement>().ok());
+                    let style = match span {
+                        Some(span) if *prompting && *animate => format!(
+                            "top: {}px; left: {}px; width: {}px; height: {}px;",
+                            span.offset_top(),
+                            span.offset_left(),
+                            span.offset_width(),
+                            span.offset_height()
+                        ),
+                        _ => "display: none;".to_string(),
+                    };
+                    let _ = highlight.set_attribute("style", &style);
+                }
+                || ()
+            },
+        );
+    }
+    // overlay mode pins the prompter over the top of the preview, near the lens
+    let overlay_prompter = use_state(|| false);
+    let overlay_opacity = use_state(|| 40u32);
+    // the browser's fullscreen view of just the prompter, text scaled up for reading at a distance
+    let prompter_fullscreen = use_state(|| false);
+    let prompter_font_px = settings.font_size * if *prompter_fullscreen { FULLSCREEN_FONT_SCALE } else { 1 };
+    {
+        let prompter_fullscreen = prompter_fullscreen.clone();
+        let tele_ref = tele_ref.clone();
+        use_effect_with((), move |_| {
+            // covers Esc and the browser's own exit controls, not just our button
+            let listener = web_sys::window().and_then(|w| w.document()).map(|document| {
+                let doc = document.clone();
+                gloo::events::EventListener::new(&document, "fullscreenchange", move |_| {
+                    let ours = doc.fullscreen_element().zip(tele_ref.get()).is_some_and(|(el, tele)| {
+                        let tele: &web_sys::Element = tele.unchecked_ref();
+                        &el == tele
+                    });
+                    prompter_fullscreen.set(ours);
+                })
+            });
+            move || drop(listener)
+        });
+    }
+    // where each word's span sits, measured after the text is laid out; the scroll
+    // loop centres the current word from this instead of estimating
+    let word_positions = use_mut_ref(Vec::<f64>::new);
+    {
+        let tele_ref = tele_ref.clone();
+        let word_positions = word_positions.clone();
+        use_effect_with(
+            ((*script).clone(), prompter_font_px, *overlay_prompter, *layout_rev),
+            move |_| {
+                if let Some(tele_el) = tele_ref.cast::<HtmlElement>() {
+                    *word_positions.borrow_mut() = word_centers(&tele_el);
+                }
+                || ()
+            },
+        );
+    }
+    let onclick_fullscreen = {
+        let tele_ref = tele_ref.clone();
+        Callback::from(move |_: ()| {
+            if let Some(tele_el) = tele_ref.cast::<web_sys::Element>() {
+                if let Err(err) = tele_el.request_fullscreen() {
+                    gloo::console::error!("fullscreen request failed:", err);
+                }
+            }
+        })
+    };
+    // manual nudges (wheel, arrow keys), in pixels on top of wherever the scroll timer puts the text
+    let nudge_px = use_mut_ref(|| 0.0f64);
+    // the scroll timer holds off until this time after a nudge
+    let nudge_hold_until = use_mut_ref(|| 0.0f64);
+    let pause_on_nudge = use_state(|| true);
+    {
+        let tele_ref = tele_ref.clone();
+        let nudge_px = nudge_px.clone();
+        let nudge_hold_until = nudge_hold_until.clone();
+        let line_px = prompter_font_px as f64 * PROMPTER_LINE_HEIGHT;
+        use_effect_with((line_px.to_bits(), *pause_on_nudge), move |(_, pause_on_nudge)| {
+            let pause_on_nudge = *pause_on_nudge;
+            let listeners = tele_ref.cast::<HtmlElement>().map(|tele_el| {
+                let nudge = Rc::new(move |tele_el: &HtmlElement, delta_px: f64| {
+                    // only count what actually moved, so nudging past either end doesn't pile up
+                    let before = tele_el.scroll_top();
+                    tele_el.set_scroll_top(before + delta_px.round() as i32);
+                    *nudge_px.borrow_mut() += (tele_el.scroll_top() - before) as f64;
+                    if pause_on_nudge {
+                        *nudge_hold_until.borrow_mut() = js_sys::Date::now() + NUDGE_HOLD_MS;
+                    }
+                });
+                // non-passive, so the page doesn't scroll along with the prompter
+                let wheel = {
+                    let nudge = nudge.clone();
+                    let el = tele_el.clone();
+                    gloo::events::EventListener::new_with_options(
+                        &tele_el,
+                        "wheel",
+                        gloo::events::EventListenerOptions::enable_prevent_default(),
+                        move |e| {
+                            let e: &web_sys::WheelEvent = e.unchecked_ref();
+                            e.prevent_default();
+                            let delta = match e.delta_mode() {
+                                web_sys::WheelEvent::DOM_DELTA_LINE => e.delta_y() * line_px,
+                                web_sys::WheelEvent::DOM_DELTA_PAGE => e.delta_y() * el.client_height() as f64,
+                                _ => e.delta_y(),
+                            };
+                            nudge(&el, delta);
+                        },
+                    )
+                };
+                let keys = {
+                    let el = tele_el.clone();
+                    gloo::events::EventListener::new_with_options(
+                        &tele_el,
+                        "keydown",
+                        gloo::events::EventListenerOptions::enable_prevent_default(),
+                        move |e| {
+                            let e: &KeyboardEvent = e.unchecked_ref();
+                            let lines = match e.key().as_str() {
+                                "ArrowUp" => -1.0,
+                                "ArrowDown" => 1.0,
+                                _ => return,
+                            };
+                            e.prevent_default();
+                            nudge(&el, lines * line_px);
+                        },
+                    )
+                };
+                (wheel, keys)
+            });
+            move || drop(listeners)
+        });
+    }
+    // (fullscreen ignores this and fills the screen)
+    let prompter_height_px = settings.prompter_lines.clamp(PROMPTER_LINES_RANGE.0, PROMPTER_LINES_RANGE.1) as f64
+        * prompter_font_px as f64
+        * PROMPTER_LINE_HEIGHT
+        + 2.0 * PROMPTER_PADDING_PX as f64;
+    let prompter_placement = if *prompter_fullscreen {
+        "background: black;".to_string()
+    } else if *overlay_prompter && !*prompter_only {
+        // (there's no preview to lay it over in teleprompter-only mode)
+        format!(
+            "position: absolute; top: 0; left: 0; right: 0; z-index: 1; box-sizing: border-box; background: rgba(0,0,0,{});",
+            *overlay_opacity as f64 / 100.0
+        )
+    } else {
+        "background: rgba(0,0,0,0.8);".to_string()
+    };
+    // handler to start/stop the teleprompter
+    let onclick_toggle = {
+        let is_prompting = is_prompting.clone();
+        Callback::from(move |_: ()| {
+            is_prompting.set(!*is_prompting);
+        })
+    };
+
+    // what gates the scroll on voice, read every frame so switching it on or off, or
+    // moving the sensitivity, never restarts (and rewinds) the prompter.
+    // The analyser only gates the scroll when voice-activated mode is on
+    let vad_gate = use_mut_ref(|| None::<(AnalyserNode, f32)>);
+    *vad_gate.borrow_mut() = if *vad_scroll && !following {
+        (*analyser_handle).clone().map(|a| (a, vad_threshold(*vad_sensitivity)))
+    } else {
+        None
+    };
+
+    // initialize recorder + preview on mount
+    {
+        let stream_handle = stream_handle.clone();
+        let status = status.clone();
+        let video_ref = video_ref.clone();
+        // a ref for the teleprompter div
+        let tele_ref_for_effect = tele_ref.clone();
+        let progress_bar_ref = progress_bar_ref.clone();
+        let current_word = current_word.clone();
+        let scroll_wpm = scroll_wpm.clone();
+        let scroll_speed = scroll_speed.clone();
+        let line_by_line = line_by_line.clone();
+        let nudge_px = nudge_px.clone();
+        let nudge_hold_until = nudge_hold_until.clone();
+        let status_seen = status_seen.clone();
+        let heard = heard.clone();
+        let lead_in_ms = settings.lead_in_secs as f64 * 1000.0;
+        let script_text = script_text.clone();
+        let word_positions = word_positions.clone();
+        let vad_gate = vad_gate.clone();
+        use_effect_with(
+            (*is_prompting, *prompter_resets, following),
+            move |(start, _, following)| {
+                // every restart begins from the first word
+                current_word.set(0);
+                *nudge_px.borrow_mut() = 0.0;
+                let show_progress = move |fraction: f64| {
+                    if let Some(bar) = progress_bar_ref.cast::<HtmlElement>() {
+                        let _ = bar.set_attribute("style", &format!("width: {:.1}%;", fraction.clamp(0.0, 1.0) * 100.0));
+                    }
+                };
+                show_progress(0.0);
+                let show_progress_tick = show_progress.clone();
+                // build optional frame loop
+                let maybe_frames: Option<FrameLoop> = if *start {
+                    // grab the element
+                    let tele_el = tele_ref_for_effect
+                        .cast::<HtmlElement>()
+                        .expect("tele_ref must be a HtmlElement");
+    
+                    // accumulator in closure
+                    let mut acc = 0.0;
+                    let mut frame = vec![0f32; 1024];
+                    let mut last_voice = f64::NEG_INFINITY;
+                    let mut shown_word = 0;
+                    // voice following: only words heard after this point count
+                    let following = *following;
+                    // the script as last seen; edits are picked up here without restarting
+                    let mut known_script = String::new();
+                    let mut script_words: Vec<String> = Vec::new();
+                    let heard_at_start = heard.borrow().split_whitespace().count();
+                    let mut heard_len = 0;
+                    let mut progress = 0.0f64;
+                    // timed scrolling waits out the lead-in; following the voice has nothing to wait for
+                    let scroll_from = js_sys::Date::now() + if following { 0.0 } else { lead_in_ms };
+                    // one step per frame, `dt_ms` after the last
+                    Some(FrameLoop::start(move |dt_ms: f64| {
+                        {
+                            let script = script_text.borrow();
+                            if *script != known_script {
+                                known_script.clone_from(&script);
+                                script_words = script.split_whitespace().map(normalize_word).collect();
+                            }
+                        }
+                        let word_count = script_words.len();
+                        let last_word = word_count.saturating_sub(1);
+                        // hold the script where it is while the recording is paused
+                        if matches!(*status_seen.borrow(), RecordingStatus::Paused) {
+                            return;
+                        }
+                        if js_sys::Date::now() < scroll_from {
+                            return;
+                        }
+                        // leave a fresh nudge alone for a moment before carrying on from it
+                        if js_sys::Date::now() < *nudge_hold_until.borrow() {
+                            return;
+                        }
+                        if following {
+                            let heard = heard.borrow();
+                            if heard.len() != heard_len {
+                                heard_len = heard.len();
+                                let spoken: Vec<String> =
+                                    heard.split_whitespace().skip(heard_at_start).map(normalize_word).collect();
+                                // revised interim guesses shouldn't pull the prompter back
+                                progress = progress.max(script_progress(&script_words, &spoken) as f64);
+                            }
+                        } else if let Some((analyser, threshold)) = &*vad_gate.borrow() {
+                            analyser.get_float_time_domain_data(&mut frame);
+                            let now = js_sys::Date::now();
+                            if rms(&frame) >= *threshold {
+                                last_voice = now;
+                            }
+                            if now - last_voice > VAD_HANGOVER_MS {
+                                return;
+                            }
+                        }
+                        if following {
+                            acc += (progress - acc) * (1.0 - (1.0 - FOLLOW_EASING).powf(dt_ms / FRAME_FALLBACK_MS as f64));
+                        } else {
+                            // compute words-per-ms from whichever pace is in charge right now
+                            let words_per_ms = *scroll_wpm.borrow() as f64 * *scroll_speed.borrow() / 60_000.0;
+                            acc += words_per_ms * dt_ms;
+                        }
+                        // acc counts words spoken so far; stop on the last one
+                        let word = (acc as usize).min(last_word);
+                        // stepping holds each line until the pace reaches the first word of the next;
+                        // otherwise the current word stays centred, falling back to an even share of
+                        // the height per word until the spans have been measured
+                        let stepped = if *line_by_line.borrow() { line_top(&tele_el, word) } else { None };
+                        let top = stepped.unwrap_or_else(|| {
+                            let positions = word_positions.borrow();
+                            let measured = if positions.len() == word_count {
+                                centered_top(&positions, acc, tele_el.client_height() as f64)
+                            } else {
+                                None
+                            };
+                            measured.unwrap_or_else(|| acc * pixels_per_word(tele_el.scroll_height() as f64, word_count))
+                        });
+                        tele_el.set_scroll_top((top + *nudge_px.borrow()) as i32);
+                        show_progress_tick(acc / word_count.max(1) as f64);
+                        if word != shown_word {
+                            shown_word = word;
+                            current_word.set(word);
+                        }
+                    }))
+                } else {
+                    None
+                };
+    
+                // always return *one* cleanup closure
+                move || {
+                    if let Some(frames) = maybe_frames {
+                        drop(frames);
+                    }
+                    show_progress(0.0);
+                }
+            },
+        ); 
+        let capture = capture_cfg.clone();
+        let media_error = media_error.clone();
+        let camera_swapped_live = camera_swapped_live.clone();
+        use_effect_with((capture, *media_retries, *prompter_only), move |(capture, _, prompter_only)| {
+            // spawn your recorder init on mount, and again whenever the devices change or on Retry
+            let cancelled = Rc::new(Cell::new(false));
+            // the running stream already has the new camera; a fresh one would end the take
+            if !std::mem::take(&mut *camera_swapped_live.borrow_mut()) && !*prompter_only {
+                spawn_local(init_recorder(
+                    capture.clone(),
+                    cancelled.clone(),
+                    media_error.clone(),
+                    stream_handle.clone(),
+                    status.clone(),
+                    video_ref.clone(),
+                ));
+            }
+            // a stream that arrives after this point is no longer wanted
+            move || cancelled.set(true)
+        });
+    }
+
+    // the preview's own sound; off by default so the speakers don't feed back into the mic.
+    // It only silences the element: the stream, and so the recording, keeps its audio
+    let preview_muted = use_state(|| true);
+    {
+        let video_ref = video_ref.clone();
+        use_effect_with((*preview_muted, (*stream_handle).clone()), move |(muted, _)| {
+            if let Some(video_el) = video_ref.cast::<HtmlVideoElement>() {
+                video_el.set_muted(*muted);
+            }
+            || ()
+        });
+    }
+
+    // release the camera/mic when the stream is replaced (device switch) or on unmount
+    use_effect_with((*stream_handle).clone(), |stream| {
+        let stream = stream.clone();
+        move || {
+            if let Some(stream) = stream {
+                stop_tracks(&stream);
             }
+        }
+    });
+
+    // the mic's audio graph goes with its stream: built when one arrives, and its
+    // AudioContext closed when the stream is replaced or dropped, or on unmount
+    {
+        let analyser_handle = analyser_handle.clone();
+        let mic_gain = mic_gain.clone();
+        use_effect_with((*stream_handle).clone(), move |stream| {
+            let graph = stream.as_ref().and_then(build_mic_graph);
+            let ctx = graph.as_ref().map(|(_, gain)| gain.ctx.clone());
+            analyser_handle.set(graph.as_ref().map(|(analyser, _)| analyser.clone()));
+            mic_gain.set(graph.map(|(_, gain)| gain));
+            move || {
+                if let Some(ctx) = ctx {
+                    let _ = ctx.close();
+                }
+            }
+        });
+    }
+    use_effect_with((settings.mic_gain.to_bits(), (*mic_gain).clone()), |(level, graph)| {
+        if let Some(graph) = graph {
+            graph.gain.gain().set_value(f64::from_bits(*level) as f32);
+        }
+        || ()
+    });
+
+    // drive the mic level meter straight from the analyser, without re-rendering
+    let level_bar_ref = use_node_ref();
+    {
+        let level_bar_ref = level_bar_ref.clone();
+        use_effect_with((*analyser_handle).clone(), move |analyser| {
+            let analyser = analyser.clone();
+            let meter = analyser.map(|analyser| {
+                let mut frame = vec![0f32; analyser.fft_size() as usize];
+                Interval::new(LEVEL_METER_MS, move || {
+                    let Some(bar) = level_bar_ref.cast::<HtmlElement>() else {
+                        return;
+                    };
+                    analyser.get_float_time_domain_data(&mut frame);
+                    let level = meter_level(&frame);
+                    let _ = bar.set_attribute("style", &format!("width: {:.1}%;", level * 100.0));
+                    bar.set_class_name(if level >= 0.99 { "level-meter-fill clipping" } else { "level-meter-fill" });
+                })
+            });
+            move || drop(meter)
+        });
+    }
+
+    // bumped whenever a camera or mic is plugged in or out
+    let device_changes = use_state(|| 0u32);
+    {
+        let device_changes = device_changes.clone();
+        use_effect_with((), move |_| {
+            let listener = web_sys::window()
+                .and_then(|w| w.navigator().media_devices().ok())
+                .map(|devices| {
+                    let seen = Rc::new(Cell::new(0u32));
+                    gloo::events::EventListener::new(&devices, "devicechange", move |_| {
+                        seen.set(seen.get() + 1);
+                        device_changes.set(seen.get());
+                    })
+                });
+            move || drop(listener)
+        });
+    }
+    // the saved camera/mic are only asked for once they're known to be there
+    let devices_restored = use_mut_ref(|| false);
+
+    // list devices and check permissions on mount, then again once a stream exists
+    // (labels need permission, and getting the stream may have just granted it),
+    // and whenever devices come and go
+    {
+        let cameras = cameras.clone();
+        let mics = mics.clone();
+        let devices_listed = devices_listed.clone();
+        let camera_access = camera_access.clone();
+        let mic_access = mic_access.clone();
+        let capture = capture.clone();
+        let saved = (settings.camera_id.clone(), settings.mic_id.clone());
+        let idle = matches!(*status, RecordingStatus::Idle);
+        let devices_restored = devices_restored.clone();
+        use_effect_with(((*stream_handle).clone(), *device_changes), move |_| {
+            spawn_local(async move {
+                match enumerate_inputs().await {
+                    Ok((found_cameras, found_mics)) => {
+                        let present = |found: &[InputDevice], id: &Option<String>| {
+                            id.as_ref().filter(|id| found.iter().any(|d| &d.id == *id)).cloned()
+                        };
+                        // before permission the list has no ids, so there's nothing to match yet
+                        let ids_known = found_cameras.iter().chain(&found_mics).any(|d| !d.id.is_empty());
+                        let next = if ids_known && !std::mem::replace(&mut *devices_restored.borrow_mut(), true) {
+                            CaptureConfig {
+                                video_device: present(&found_cameras, &saved.0),
+                                audio_device: present(&found_mics, &saved.1),
+                                ..(*capture).clone()
+                            }
+                        } else {
+                            // a device in use was unplugged: back to the default (but not mid-take)
+                            CaptureConfig {
+                                video_device: present(&found_cameras, &capture.video_device),
+                                audio_device: present(&found_mics, &capture.audio_device),
+                                ..(*capture).clone()
+                            }
+                        };
+                        if ids_known && idle && next != *capture {
+                            capture.set(next);
+                        }
+                        cameras.set(found_cameras);
+                        mics.set(found_mics);
+                        devices_listed.set(true);
+                    }
+                    Err(err) => gloo::console::error!("enumerateDevices error:", err),
+                }
+                camera_access.set(query_access("camera").await);
+                mic_access.set(query_access("microphone").await);
+            });
+            || ()
+        });
+    }
+
+    // (re)build the recorder whenever the stream arrives or the overlays are toggled
+    {
+        // each finished take becomes the playback
+        let on_take = {
+            let status = status.clone();
+            let takes = takes.clone();
+            let takes_seen = takes_seen.clone();
+            let take_count = take_count.clone();
+            let recorder_error = recorder_error.clone();
+            let discard_take = discard_take.clone();
+            let wpm_samples = wpm_samples.clone();
+            let on_recording = on_recording.clone();
+            let segments = segments.clone();
+            let start_pending = start_pending.clone();
+            Callback::from(move |take: Option<Take>| {
+                status.set(RecordingStatus::Idle);
+                let samples = std::mem::take(&mut *wpm_samples.borrow_mut());
+                // a split carries on into the next part; any other stop ends the run of parts
+                let segment = {
+                    let mut segments = segments.borrow_mut();
+                    let part = (segments.part > 0).then_some(segments.part);
+                    if std::mem::take(&mut segments.splitting) {
+                        segments.part += 1;
+                        let ends_at = take.as_ref().and_then(|t| t.chunks.last()).map_or(0.0, |(ends_at, _)| *ends_at);
+                        segments.done_secs += ends_at / 1000.0;
+                        // the next part starts as soon as this one is in; only the moment
+                        // between stop and start goes unrecorded
+                        start_pending.set(true);
+                    } else {
+                        *segments = Segments::default();
+                    }
+                    part
+                };
+                if std::mem::take(&mut *discard_take.borrow_mut()) {
+                    return;
+                }
+                let Some(take) = take else {
+                    recorder_error.set(Some("Couldn't put the take together; it was lost.".to_string()));
+                    return;
+                };
+                // no playback for a take with nothing in it; an empty player only confuses
+                if take.too_short() {
+                    recorder_error.set(Some("Recording too short; nothing was kept.".to_string()));
+                    return;
+                }
+                let Take { blob, mime, chunks } = take;
+                match Url::create_object_url_with_blob(&blob) {
+                    Err(err) => report_recorder_error(&recorder_error, "open the take for playback", &err),
+                    Ok(url) => {
+                        // the last chunk ends where the recording did
+                        let duration_secs = chunks.last().map_or(0.0, |(ends_at, _)| ends_at.max(0.0) / 1000.0);
+                        *take_count.borrow_mut() += 1;
+                        on_recording.borrow().emit(RecordingResult {
+                            blob_url: url.clone(),
+                            duration_secs,
+                            mime_type: mime.clone(),
+                        });
+                        let mut list = takes_seen.borrow().clone();
+                        list.push(RecordedTake {
+                            number: *take_count.borrow(),
+                            url,
+                            blob,
+                            mime,
+                            chunks: Rc::new(chunks),
+                            duration_secs,
+                            trim: (0, duration_secs.ceil() as u32),
+                            save_status: None,
+                            upload_status: None,
+                            wpm_samples: Rc::new(samples),
+                            player: NodeRef::default(),
+                            rating: 0,
+                            segment,
+                        });
+                        takes.set(list);
+                    }
+                }
+            })
+        };
+        // the recorder failed mid-take: say so, and stand the timer and prompter down.
+        // The take itself still arrives through on_take with whatever was recorded
+        let on_recorder_failed = {
+            let status = status.clone();
+            let is_prompting = is_prompting.clone();
+            let recorder_error = recorder_error.clone();
+            Callback::from(move |msg: String| {
+                recorder_error.set(Some(format!(
+                    "Recording stopped unexpectedly ({msg}). Anything recorded so far is kept as a take; press Record to try again."
+                )));
+                status.set(RecordingStatus::Idle);
+                is_prompting.set(false);
+            })
+        };
+        let attach_recorder = {
+            let recorder_handle = recorder_handle.clone();
+            let recorder_error = recorder_error.clone();
+            let bitrate = settings.bitrate;
+            move |stream: &MediaStream, mime: Option<&str>| match Recorder::new(
+                stream,
+                mime,
+                bitrate,
+                on_take.clone(),
+                on_recorder_failed.clone(),
+            ) {
+                Ok(recorder) => recorder_handle.set(Some(recorder)),
+                Err(err) => report_recorder_error(&recorder_error, "set up the recorder", &err),
+            }
+        };
+        let video_ref = video_ref.clone();
+        let overlay_canvas_ref = overlay_canvas_ref.clone();
+        let lower_third_cfg = lower_third_cfg.clone();
+        let stamp_cfg = stamp_cfg.clone();
+        let rec_started = rec_started.clone();
+        let encoder_report = encoder_report.clone();
+        // record the boosted mic when there is one
+        let recorded = (*mic_gain).as_ref().map(|gain| gain.stream.clone()).or_else(|| (*stream_handle).clone());
+        use_effect_with(
+            (recorded, overlay_active, (*recording_type).clone(), *prefer_hw_encoding, settings.bitrate),
+            move |(stream, overlay_on, mime, prefer_hw, _)| {
+                let mut compositor = None;
+                let mut composite_stream = None;
+                if let Some(stream) = stream {
+                    let mime = mime.clone();
+                    let video = video_ref.cast::<HtmlVideoElement>();
+                    let (width, height) = video
+                        .as_ref()
+                        .map(|v| (v.video_width(), v.video_height()))
+                        .filter(|(w, h)| *w > 0 && *h > 0)
+                        .unwrap_or((1280, 720));
+                    let canvas = overlay_canvas_ref.cast::<HtmlCanvasElement>();
+                    match (*overlay_on, video, canvas) {
+                        (true, Some(video), Some(canvas)) => {
+                            compositor = start_compositor(
+                                video,
+                                canvas.clone(),
+                                lower_third_cfg,
+                                stamp_cfg,
+                                rec_started,
+                            );
+                            // canvas video + the original mic track(s)
+                            if let (Some(_), Ok(composite)) = (
+                                &compositor,
+                                canvas.capture_stream_with_frame_request_rate(COMPOSITOR_FPS as f64),
+                            ) {
+                                for track in stream.get_audio_tracks().iter() {
+                                    composite.add_track(&track.unchecked_into::<MediaStreamTrack>());
+                                }
+                                attach_recorder(&composite, mime.as_deref());
+                                composite_stream = Some(composite);
+                            } else {
+                                attach_recorder(stream, mime.as_deref());
+                            }
+                        }
+                        _ => attach_recorder(stream, mime.as_deref()),
+                    }
 
-            recorder_handle.set(Some(recorder));
-            status.set(RecordingStatus::Idle);
-        }
-        Err(err) => {
-            gloo::console::error!("getUserMedia error:", err);
-        }
+                    let prefer_hw = *prefer_hw;
+                    spawn_local(async move {
+                        let report = match mime {
+                            // MediaCapabilities only tells us about video encoders
+                            Some(mime) if mime.starts_with("audio/") => EncoderReport::Unknown,
+                            Some(mime) => match probe_power_efficient(&mime, width, height).await {
+                                Some(true) => EncoderReport::Hardware,
+                                Some(false) if prefer_hw => EncoderReport::SoftwareFallback,
+                                Some(false) => EncoderReport::Software,
+                                None => EncoderReport::Unknown,
+                            },
+                            None => EncoderReport::Unknown,
+                        };
+                        encoder_report.set(report);
+                    });
+                }
+                move || {
+                    drop(compositor);
+                    // only the canvas tracks are ours; the mic track belongs to the camera stream
+                    if let Some(composite) = composite_stream {
+                        for track in composite.get_video_tracks().iter() {
+                            track.unchecked_into::<MediaStreamTrack>().stop();
+                        }
+                    }
+                }
+            },
+        );
     }
-}
 
-#[derive(Clone, PartialEq)]
-enum RecordingStatus {
-    Idle,
-    Recording,
-    Paused,
-}
-impl Display for RecordingStatus {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                RecordingStatus::Recording => "Recording",
-                RecordingStatus::Paused => "Paused",
-                RecordingStatus::Idle => "Idle",
-            }
-        )
+    // The recorder can change state on its own (e.g. it stops when the camera
+    // track ends), so poll it and correct `status` whenever the two disagree.
+    {
+        let status = status.clone();
+        let status_seen = status_seen.clone();
+        use_effect_with((*recorder_handle).clone(), move |recorder| {
+            let poll = recorder.clone().map(|rec| {
+                Interval::new(STATUS_POLL_MS, move || {
+                    let was = status_seen.borrow().clone();
+                    let Some(actual) = poll_status(&mut status_seen.borrow_mut(), rec.status()) else {
+                        return;
+                    };
+                    gloo::console::warn!(format!("status desync: UI says {was}, recorder says {actual}"));
+                    status.set(actual);
+                })
+            });
+            move || drop(poll)
+        });
     }
-}
 
-#[function_component(App)]
-pub fn app() -> Html {
-    // Live WPM using the browser speech to text API
-    let wpm = use_state(|| 120u32);
-    let recog_ref = use_mut_ref(|| None::<web_sys::SpeechRecognition>);
-        // on-mount: start recognition once
-    let wpm_recog = wpm.clone();
-    use_effect_with((), move |_| {
-        if let Ok(recog) = SpeechRecognition::new() {
-            web_sys::console::log_1(&"⚡ SR effect mounted".into());
-            // Try to construct SR and log success or failure
-            match SpeechRecognition::new() {
-                Ok(recog) => {
-                    web_sys::console::log_1(&"✅ SpeechRecognition::new() succeeded".into());
-                    // … your existing setup for recog …
-                }
-                Err(err) => {
-                    web_sys::console::error_1(&format!("❌ SpeechRecognition::new() failed: {:?}", err).into());
-                }
-            }
-            // configure it
-            recog.set_continuous(true);
-            recog.set_interim_results(true);
-    
-            // stash it in our ref so we can stop it later
-            recog_ref.borrow_mut().replace(recog.clone());
-    
-            // time markers
-            let start_time = js_sys::Date::now();
-    
-            // onresult handler
-            let on_result = Closure::wrap(Box::new(move |e: SpeechRecognitionEvent| {
-                let mut transcript = String::new();
-                let results = e.results() .expect("SpeechRecognitionEvent should always have results");
-                // print the results
-                web_sys::console::log_1(&format!("Results: {:?}", results).into());
-                for i in 0..results.length() {
-                    let res = results.get(i).unwrap();
-                    transcript.push_str(&res.get(0).unwrap().transcript());
-                    transcript.push(' ');
-                }
-                web_sys::console::log_1(&format!("Transcript so far: “{}”", transcript).into());
-
-                let words   = transcript.split_whitespace().count() as f64;
-                let elapsed = (js_sys::Date::now() - start_time) / 1000.0;
-                if elapsed > 1.0 {
-                    let current_wpm = (words / elapsed) * 60.0;
-                    wpm_recog.set(current_wpm.round() as u32);
-                }
-            }) as Box<dyn FnMut(_)>);
-    
-            recog.set_onresult(Some(on_result.as_ref().unchecked_ref()));
-            on_result.forget();
-    
-            // start recognition
-            let _ = recog.start();
-        }
-    
-        // **Remember**: only two arguments to use_effect_with,
-        // so we return our teardown from inside this one closure:
-        move || {
-            if let Some(r) = recog_ref.borrow_mut().take() {
-                let _ = r.stop();
-            }
-        }
-    });
-    // refs & state
-    let video_ref = use_node_ref();
-    let playback_url = use_state(|| None::<String>);
-    let recorder_handle = use_state(|| None::<MediaRecorder>);
-    let status = use_state(|| RecordingStatus::Idle);
-    let chunks = use_state(Vec::new);
+    let self_test = use_state(|| None::<SelfTestState>);
+    let onclick_self_test = {
+        let self_test = self_test.clone();
+        let mime = (*recording_type).clone();
+        let capture = capture_cfg.clone();
+        let bitrate = settings.bitrate;
+        Callback::from(move |_: ()| {
+            let self_test = self_test.clone();
+            let capture = capture.clone();
+            let mime = mime.clone();
+            self_test.set(Some(SelfTestState::Running));
+            spawn_local(async move {
+                self_test.set(Some(match run_self_test(capture, mime, bitrate).await {
+                    Ok(()) => SelfTestState::Passed,
+                    Err((stage, msg)) => SelfTestState::Failed(stage, msg),
+                }));
+            });
+        })
+    };
 
-    let script = use_state(|| String::new());
-    let is_prompting = use_state(|| false);
-    let tele_ref = use_node_ref();
-    // handler to start/stop the teleprompter
-    let onclick_toggle = {
-        let is_prompting = is_prompting.clone();
-        Callback::from(move |_| {
-            is_prompting.set(!*is_prompting);
+    // "Test mic": a short sample played straight back, never added to the takes
+    let mic_test = use_state(|| None::<MicTestState>);
+    let mic_testing = matches!(*mic_test, Some(MicTestState::Recording | MicTestState::Playing));
+    let onclick_mic_test = {
+        let mic_test = mic_test.clone();
+        // what a take would record, so the sample has the gain applied too
+        let stream = (*mic_gain).as_ref().map(|gain| gain.stream.clone()).or_else(|| (*stream_handle).clone());
+        let mic_gain = (*mic_gain).clone();
+        Callback::from(move |_: ()| {
+            let Some(stream) = stream.clone() else { return };
+            if let Some(graph) = &mic_gain {
+                let _ = graph.ctx.resume();
+            }
+            let mic_test = mic_test.clone();
+            mic_test.set(Some(MicTestState::Recording));
+            spawn_local(async move {
+                let playing = mic_test.clone();
+                let result = run_mic_test(&stream, move || playing.set(Some(MicTestState::Playing))).await;
+                mic_test.set(result.err().map(|err| MicTestState::Failed(js_error_message(&err))));
+            });
         })
     };
 
-    // initialize recorder + preview on mount
+    // elapsed recording time: ticks while recording, holds while paused, resets when idle
+    let elapsed_secs = use_state(|| 0u32);
     {
-        let recorder_handle = recorder_handle.clone();
-        let status = status.clone();
-        let chunks = chunks.clone();
-        let video_ref = video_ref.clone();
-        // a ref for the teleprompter div
-        let tele_ref_for_effect = tele_ref.clone();
-        use_effect_with(
-            (*is_prompting, *wpm, (*script).clone()),
-            move |(start, wpm_val, _script)| {
-                // build optional interval
-                let maybe_interval: Option<Interval> = if *start {
-                    // compute bytes-per-ms
-                    let words_per_ms = *wpm_val as f64 / 60_000.0;
-                    // grab the element
-                    let tele_el = tele_ref_for_effect
-                        .cast::<HtmlElement>()
-                        .expect("tele_ref must be a HtmlElement");
-    
-                    // accumulator in closure
-                    let mut acc = 0.0;
-                    // create the interval
-                    Some(Interval::new(50, move || {
-                        acc += words_per_ms * 50.0;
-                        tele_el.set_scroll_top((acc * 20.0) as i32);
+        let active_clock = active_clock.clone();
+        use_effect_with((*status).clone(), move |status| {
+            let now = js_sys::Date::now();
+            match status {
+                RecordingStatus::Paused => active_clock.borrow_mut().pause(now),
+                _ => active_clock.borrow_mut().resume(now),
+            }
+            || ()
+        });
+    }
+    {
+        let elapsed_secs = elapsed_secs.clone();
+        let last_result_at = last_result_at.clone();
+        let no_speech = no_speech.clone();
+        let speech_supported = *speech_supported;
+        let live_wpm_seen = live_wpm_seen.clone();
+        let wpm_samples = wpm_samples.clone();
+        use_effect_with((*status).clone(), move |status| {
+            // a (re)started recording gets the full grace period before we worry
+            *last_result_at.borrow_mut() = js_sys::Date::now();
+            no_speech.set(false);
+            let ticker = match status {
+                RecordingStatus::Recording => {
+                    let mut secs = *elapsed_secs;
+                    Some(Interval::new(1000, move || {
+                        secs += 1;
+                        elapsed_secs.set(secs);
+                        if speech_supported {
+                            let mut samples = wpm_samples.borrow_mut();
+                            if samples.len() >= WPM_SAMPLES_MAX {
+                                samples.remove(0);
+                            }
+                            samples.push((secs, *live_wpm_seen.borrow()));
+                        }
+                        let quiet_ms = js_sys::Date::now() - *last_result_at.borrow();
+                        if speech_supported && quiet_ms > NO_SPEECH_HINT_MS {
+                            no_speech.set(true);
+                        }
                     }))
-                } else {
+                }
+                RecordingStatus::Paused => None,
+                RecordingStatus::Idle => {
+                    elapsed_secs.set(0);
                     None
-                };
-    
-                // always return *one* cleanup closure
-                move || {
-                    if let Some(interval) = maybe_interval {
-                        drop(interval);
-                    }
                 }
-            },
-        ); 
-        use_effect_with((), move |_| {
-            // spawn your recorder init exactly once
-            spawn_local(init_recorder(
-                recorder_handle.clone(),
-                status.clone(),
-                chunks.clone(),
-                video_ref.clone(),
-            ));
-            // return a no-op tear-down
-            || ()
+            };
+            move || drop(ticker)
         });
     }
 
@@ -244,106 +2671,2072 @@ pub fn app() -> Html {
     let onclick_start = {
         let recorder_handle = recorder_handle.clone();
         let status = status.clone();
-        Callback::from(move |_| {
-            if let Some(rec) = recorder_handle.as_ref() {
-                rec.start().unwrap();
+        let rec_started = rec_started.clone();
+        let start_pending = start_pending.clone();
+        let recorder_error = recorder_error.clone();
+        let prompter_only = *prompter_only;
+        let wpm_samples = wpm_samples.clone();
+        let mic_gain = (*mic_gain).clone();
+        Callback::from(move |_: ()| match recorder_handle.as_ref() {
+            // nothing to record with (shortcuts and the palette can still get here)
+            _ if prompter_only => {}
+            Some(rec) => {
+                start_pending.set(false);
+                // a context made before any click may still be suspended, and would record silence
+                if let Some(graph) = &mic_gain {
+                    let _ = graph.ctx.resume();
+                }
+                if let Err(err) = rec.start() {
+                    report_recorder_error(&recorder_error, "start recording", &err);
+                    return;
+                }
+                recorder_error.set(None);
+                rec_started.borrow_mut().replace(js_sys::Date::now());
+                wpm_samples.borrow_mut().clear();
                 status.set(RecordingStatus::Recording);
             }
+            None => start_pending.set(!*start_pending),
         })
     };
+    {
+        let onclick_start = onclick_start.clone();
+        let start_pending = start_pending.clone();
+        let media_failed = media_error.is_some();
+        use_effect_with((recorder_handle.is_some(), *start_pending, media_failed), move |(ready, pending, failed)| {
+            if *pending && *failed {
+                // the camera isn't coming; don't start by surprise after a Retry
+                start_pending.set(false);
+            } else if *pending && *ready {
+                onclick_start.emit(());
+            }
+            || ()
+        });
+    }
     let onclick_pause = {
         let recorder_handle = recorder_handle.clone();
         let status = status.clone();
-        Callback::from(move |_| {
+        let recorder_error = recorder_error.clone();
+        Callback::from(move |_: ()| {
             if let Some(rec) = recorder_handle.as_ref() {
-                rec.pause().unwrap();
-                status.set(RecordingStatus::Paused);
+                match rec.pause() {
+                    Ok(()) => status.set(RecordingStatus::Paused),
+                    Err(err) => report_recorder_error(&recorder_error, "pause", &err),
+                }
             }
         })
     };
-    let onclick_stop = {
+    let onclick_resume = {
         let recorder_handle = recorder_handle.clone();
         let status = status.clone();
-        let chunks = chunks.clone();
-        let playback_url = playback_url.clone();
-        Callback::from(move |_| {
+        let recorder_error = recorder_error.clone();
+        Callback::from(move |_: ()| {
+            if let Some(rec) = recorder_handle.as_ref() {
+                match rec.resume() {
+                    Ok(()) => status.set(RecordingStatus::Recording),
+                    Err(err) => report_recorder_error(&recorder_error, "resume", &err),
+                }
+            }
+        })
+    };
+    // the recorder merges the chunks once it stops, and `on_take` adds it to `takes`
+    let onclick_stop = {
+        let recorder_handle = recorder_handle.clone();
+        let rec_started = rec_started.clone();
+        let recorder_error = recorder_error.clone();
+        Callback::from(move |_: ()| {
             if let Some(rec) = recorder_handle.as_ref() {
-                rec.stop().unwrap();
+                if let Err(err) = rec.stop() {
+                    report_recorder_error(&recorder_error, "stop recording", &err);
+                }
             }
-            // After onstop fires and status becomes Idle, merge blobs
-            if matches!(*status, RecordingStatus::Idle) {
-                // Merge blobs into one video blob
-                let arr = js_sys::Array::new();
-                for blob in chunks.iter() {
-                    arr.push(blob);
+            rec_started.borrow_mut().take();
+        })
+    };
+
+    // camera picker: while idle this just re-initializes the stream; mid-take it tries to
+    // swap the camera under the running recorder, else splits the take around the switch
+    let onchange_camera = {
+        let capture = capture.clone();
+        let status = status.clone();
+        let stream_handle = stream_handle.clone();
+        let camera_swapped_live = camera_swapped_live.clone();
+        let onclick_stop = onclick_stop.clone();
+        let recorder_handle = recorder_handle.clone();
+        let start_pending = start_pending.clone();
+        let recorder_error = recorder_error.clone();
+        let take_split_notice = take_split_notice.clone();
+        let settings = settings.clone();
+        Callback::from(move |video_device: Option<String>| {
+            let resolution = settings.resolution;
+            let frame_rate = settings.frame_rate;
+            settings.set(Settings { camera_id: video_device.clone(), ..(*settings).clone() });
+            let next = CaptureConfig { video_device: video_device.clone(), ..(*capture).clone() };
+            let stream = match (&*status, &*stream_handle) {
+                (RecordingStatus::Idle, _) | (_, None) => {
+                    capture.set(next);
+                    return;
+                }
+                (_, Some(stream)) => stream.clone(),
+            };
+            let capture = capture.clone();
+            let camera_swapped_live = camera_swapped_live.clone();
+            let onclick_stop = onclick_stop.clone();
+            let recorder_handle = recorder_handle.clone();
+            let start_pending = start_pending.clone();
+            let recorder_error = recorder_error.clone();
+            let take_split_notice = take_split_notice.clone();
+            spawn_local(async move {
+                match swap_camera(&stream, video_device.as_deref(), resolution, frame_rate, overlay_active).await {
+                    Ok(CameraSwap::Live) => {
+                        *camera_swapped_live.borrow_mut() = true;
+                        capture.set(next);
+                    }
+                    Ok(CameraSwap::NeedsRestart) => {
+                        gloo::console::info!("can't swap cameras under this recorder; starting a new take");
+                        take_split_notice.set(true);
+                        onclick_stop.emit(());
+                        // wait for the recorder built on the new stream, then carry on recording
+                        recorder_handle.set(None);
+                        start_pending.set(true);
+                        capture.set(next);
+                    }
+                    Err(err) => report_recorder_error(&recorder_error, "switch cameras", &err),
+                }
+            });
+        })
+    };
+
+    // auto-stop: the recording timer ticks `elapsed_secs`, and reaching the limit takes the
+    // Stop path; every part of a split recording counts towards it. The size/length cap
+    // splits the same way, but marks the stop so recording carries on
+    {
+        let onclick_stop = onclick_stop.clone();
+        let recorder_handle = recorder_handle.clone();
+        let segments = segments.clone();
+        let recording = matches!(*status, RecordingStatus::Recording);
+        use_effect_with(
+            (*elapsed_secs, settings.max_duration_secs, settings.split_every_secs, settings.split_at_mb, recording),
+            move |(elapsed, limit, split_secs, split_mb, recording)| {
+                if *recording {
+                    let session_secs = segments.borrow().done_secs.round() as u32 + *elapsed;
+                    let bytes = recorder_handle.as_ref().map_or(0.0, |rec| rec.recorded_bytes());
+                    if limit.is_some_and(|limit| session_secs >= limit) {
+                        onclick_stop.emit(());
+                    } else if split_secs.is_some_and(|secs| *elapsed >= secs)
+                        || split_mb.is_some_and(|mb| bytes >= mb as f64 * 1024.0 * 1024.0)
+                    {
+                        let mut segments = segments.borrow_mut();
+                        segments.splitting = true;
+                        segments.part = segments.part.max(1);
+                        drop(segments);
+                        onclick_stop.emit(());
+                    }
                 }
-                if let Ok(final_blob) = web_sys::Blob::new_with_blob_sequence(&arr) {
-                    let url = Url::create_object_url_with_blob(&final_blob).unwrap();
-                    playback_url.set(Some(url));
+                || ()
+            },
+        );
+    }
+
+    // back to the first word without stopping the prompter; the lead-in runs again
+    let onclick_restart_prompter = {
+        let tele_ref = tele_ref.clone();
+        let current_word = current_word.clone();
+        let prompter_resets = prompter_resets.clone();
+        Callback::from(move |_: ()| {
+            if let Some(tele_el) = tele_ref.cast::<HtmlElement>() {
+                tele_el.set_scroll_top(0);
+            }
+            current_word.set(0);
+            prompter_resets.set(*prompter_resets + 1);
+        })
+    };
+
+    // throw the latest take away and get ready for another, rewinding the prompter
+    let onclick_retake = {
+        let takes = takes.clone();
+        let delete_take = delete_take.clone();
+        let onclick_restart_prompter = onclick_restart_prompter.clone();
+        Callback::from(move |_: ()| {
+            if let Some(last) = takes.last() {
+                delete_take.emit(last.number);
+            }
+            onclick_restart_prompter.emit(());
+        })
+    };
+
+    // back to a clean slate: recording stopped and discarded, takes and transcript gone,
+    // prompter rewound, pace back to the default, and a fresh camera stream and recorder.
+    // Timers belong to effects keyed on what this resets, so they go with it.
+    let onclick_reset = {
+        let recorder_handle = recorder_handle.clone();
+        let discard_take = discard_take.clone();
+        let segments = segments.clone();
+        let rec_started = rec_started.clone();
+        let start_pending = start_pending.clone();
+        let recorder_error = recorder_error.clone();
+        let takes = takes.clone();
+        let take_count = take_count.clone();
+        let transcript = transcript.clone();
+        let speech_resets = speech_resets.clone();
+        let live_wpm = live_wpm.clone();
+        let settings = settings.clone();
+        let auto_pace = auto_pace.clone();
+        let is_prompting = is_prompting.clone();
+        let tele_ref = tele_ref.clone();
+        let current_word = current_word.clone();
+        let nudge_px = nudge_px.clone();
+        let prompter_resets = prompter_resets.clone();
+        let media_retries = media_retries.clone();
+        let default_wpm = props.default_wpm;
+        Callback::from(move |_: ()| {
+            if let Some(rec) = recorder_handle.as_ref() {
+                if rec.status() != RecordingStatus::Idle {
+                    *discard_take.borrow_mut() = true;
+                    if let Err(err) = rec.stop() {
+                        gloo::console::error!("stop during reset failed:", err);
+                    }
                 }
             }
+            rec_started.borrow_mut().take();
+            start_pending.set(false);
+            *segments.borrow_mut() = Segments::default();
+            recorder_error.set(None);
+            for take in takes.iter() {
+                let _ = Url::revoke_object_url(&take.url);
+            }
+            takes.set(Vec::new());
+            *take_count.borrow_mut() = 0;
+            transcript.set(Transcript::default());
+            speech_resets.set(*speech_resets + 1);
+            live_wpm.set(default_wpm);
+            auto_pace.set(false);
+            settings.set(Settings { target_wpm: default_wpm, ..(*settings).clone() });
+            is_prompting.set(false);
+            if let Some(tele_el) = tele_ref.cast::<HtmlElement>() {
+                tele_el.set_scroll_top(0);
+            }
+            *nudge_px.borrow_mut() = 0.0;
+            current_word.set(0);
+            prompter_resets.set(*prompter_resets + 1);
+            // a new stream replaces the old one, whose tracks are then stopped
+            media_retries.set(*media_retries + 1);
         })
     };
 
+    // quick-settings palette (Ctrl/Cmd+K): every action that makes sense right now
+    let palette_open = use_state(|| false);
+    {
+        let palette_open = palette_open.clone();
+        use_effect_with((), move |_| {
+            let listener = web_sys::window().map(|window| {
+                gloo::events::EventListener::new(&window, "keydown", move |e| {
+                    let e: &KeyboardEvent = e.unchecked_ref();
+                    if (e.ctrl_key() || e.meta_key()) && e.key().eq_ignore_ascii_case("k") {
+                        e.prevent_default();
+                        palette_open.set(true);
+                    }
+                })
+            });
+            move || drop(listener)
+        });
+    }
+    // keyboard shortcuts, for presenters who can't reach the mouse on camera:
+    // Space records/pauses/resumes, S stops, T toggles the teleprompter.
+    // The listener outlives renders, so it reads what each key does from here
+    let shortcuts = use_mut_ref(|| [None::<Callback<()>>, None, None]);
+    *shortcuts.borrow_mut() = [
+        match *status {
+            RecordingStatus::Idle => recorder_handle.is_some().then(|| onclick_start.clone()),
+            RecordingStatus::Recording => Some(onclick_pause.clone()),
+            RecordingStatus::Paused => Some(onclick_resume.clone()),
+        },
+        (!matches!(*status, RecordingStatus::Idle)).then(|| onclick_stop.clone()),
+        Some(onclick_toggle.clone()),
+    ];
+    {
+        let shortcuts = shortcuts.clone();
+        use_effect_with((), move |_| {
+            let listener = web_sys::window().map(|window| {
+                gloo::events::EventListener::new_with_options(
+                    &window,
+                    "keydown",
+                    gloo::events::EventListenerOptions::enable_prevent_default(),
+                    move |e| {
+                        let e: &KeyboardEvent = e.unchecked_ref();
+                        if e.ctrl_key() || e.meta_key() || e.alt_key() || e.repeat() {
+                            return;
+                        }
+                        // typing the script (or into any field) isn't a command
+                        let typing = e
+                            .target()
+                            .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+                            .is_some_and(|el| matches!(el.tag_name().as_str(), "TEXTAREA" | "INPUT" | "SELECT"));
+                        if typing {
+                            return;
+                        }
+                        let slot = match e.key().as_str() {
+                            " " => 0,
+                            "s" | "S" => 1,
+                            "t" | "T" => 2,
+                            _ => return,
+                        };
+                        // Space would also press whichever button has focus
+                        e.prevent_default();
+                        let action = shortcuts.borrow()[slot].clone();
+                        if let Some(action) = action {
+                            action.emit(());
+                        }
+                    },
+                )
+            });
+            move || drop(listener)
+        });
+    }
+    let toggle = |flag: &UseStateHandle<bool>| {
+        let flag = flag.clone();
+        Callback::from(move |_: ()| flag.set(!*flag))
+    };
+    let idle = matches!(*status, RecordingStatus::Idle);
+    let script_words = script.split_whitespace().count();
+    // CSS only: the recorder reads the stream (or the compositor's canvas pixels), never this transform
+    let preview_flip = if settings.mirror_preview { "transform: scaleX(-1);" } else { "" };
+    // the whole recording so far, across the parts of a split one
+    let session_secs = segments.borrow().done_secs.round() as u32 + *elapsed_secs;
+    // the takes list as reviewed: filtered by rating, best first if asked
+    let mut shown_takes: Vec<&RecordedTake> = takes.iter().filter(|t| t.rating >= *min_rating).collect();
+    if *best_first {
+        // stable, so equal ratings stay in recording order
+        shown_takes.sort_by_key(|t| std::cmp::Reverse(t.rating));
+    }
+    let hidden_takes = takes.len() - shown_takes.len();
+    // kept takes getting close to what the browser will store for the page
+    let storage_warning = (*storage_estimate)
+        .filter(|(used, allowed)| settings.take_storage != TakeStorage::Off && near_quota(*used, *allowed));
+    // keeping takes by path needs somewhere to have saved them
+    let storage_choices: Vec<TakeStorage> =
+        TakeStorage::ALL.into_iter().filter(|choice| in_tauri() || *choice != TakeStorage::PathOnly).collect();
+    let palette_commands: Vec<PaletteCommand> = [
+        (idle && recorder_handle.is_some(), "Start recording", onclick_start.clone()),
+        (matches!(*status, RecordingStatus::Recording), "Pause recording", onclick_pause.clone()),
+        (matches!(*status, RecordingStatus::Paused), "Resume recording", onclick_resume.clone()),
+        (!idle, "Stop & preview", onclick_stop.clone()),
+        (idle && !takes.is_empty(), "Discard & retake", onclick_retake.clone()),
+        (
+            true,
+            if *is_prompting { "Stop teleprompter" } else { "Start teleprompter" },
+            onclick_toggle.clone(),
+        ),
+        (*is_prompting, "Restart teleprompter", onclick_restart_prompter.clone()),
+        (!*prompter_fullscreen, "Fullscreen teleprompter", onclick_fullscreen.clone()),
+        (!script.trim().is_empty(), "Export script (PDF)", {
+            let script = script.clone();
+            Callback::from(move |_| print_script(&script))
+        }),
+        (*speech_supported, "Toggle auto-pace", toggle(&auto_pace)),
+        (*speech_supported, "Toggle follow my voice", toggle(&follow_voice)),
+        (!following, "Toggle scroll only while speaking", toggle(&vad_scroll)),
+        (true, "Toggle overlay prompter", toggle(&overlay_prompter)),
+        (true, "Toggle mirrored prompter text", {
+            let settings = settings.clone();
+            Callback::from(move |_| {
+                settings.set(Settings { mirror_prompter: !settings.mirror_prompter, ..(*settings).clone() })
+            })
+        }),
+        (true, "Toggle mirrored preview", {
+            let settings = settings.clone();
+            Callback::from(move |_| {
+                settings.set(Settings { mirror_preview: !settings.mirror_preview, ..(*settings).clone() })
+            })
+        }),
+        (idle, "Toggle lower third", {
+            let lower_third = lower_third.clone();
+            Callback::from(move |_| {
+                lower_third.set(LowerThird { enabled: !lower_third.enabled, ..(*lower_third).clone() })
+            })
+        }),
+        (idle, "Toggle prefer hardware encoding", toggle(&prefer_hw_encoding)),
+        (true, "Toggle break reminder", toggle(&break_reminder_on)),
+    ]
+    .into_iter()
+    .filter(|(available, _, _)| *available)
+    .map(|(_, label, action)| PaletteCommand { label, action })
+    .collect();
+
     html! {
         <main class="container">
+            {
+                if *palette_open {
+                    html! {
+                        <CommandPalette
+                            commands={palette_commands}
+                            on_close={Callback::from({
+                                let palette_open = palette_open.clone();
+                                move |_| palette_open.set(false)
+                            })}
+                        />
+                    }
+                } else {
+                    html! {}
+                }
+            }
             <h1>{"Born-Edited Recorder (Audio+Video)"}</h1>
-            <p>{ format!("Live WPM: {}", *wpm) }</p>
+            <label title="Rehearse with the prompter alone; the camera and mic aren't touched">
+              <input
+                type="checkbox"
+                checked={*prompter_only}
+                disabled={!idle}
+                onchange={Callback::from({
+                  let prompter_only = prompter_only.clone();
+                  let stream_handle = stream_handle.clone();
+                  let recorder_handle = recorder_handle.clone();
+                  let analyser_handle = analyser_handle.clone();
+                  let media_error = media_error.clone();
+                  let devices_listed = devices_listed.clone();
+                  let start_pending = start_pending.clone();
+                  move |_| {
+                    if !*prompter_only {
+                      // let go of the camera and mic; dropping the stream stops its tracks
+                      recorder_handle.set(None);
+                      analyser_handle.set(None);
+                      stream_handle.set(None);
+                      media_error.set(None);
+                      devices_listed.set(false);
+                      start_pending.set(false);
+                    }
+                    prompter_only.set(!*prompter_only);
+                  }
+                })}
+              />
+              { " Teleprompter only (no recording)" }
+            </label>
+            <details class="settings-panel">
+              <summary>{"Settings"}</summary>
+              if *speech_supported {
+                <label>
+                  { "Speech language " }
+                  <select onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |e: Event| {
+                      let speech_lang = e.target_unchecked_into::<web_sys::HtmlSelectElement>().value();
+                      settings.set(Settings { speech_lang, ..(*settings).clone() });
+                    }
+                  })}>
+                    { for SPEECH_LANGUAGES.iter().map(|(tag, label)| html! {
+                      <option value={*tag} selected={settings.speech_lang == *tag}>{ *label }</option>
+                    }) }
+                  </select>
+                </label>
+                <label title="Words the recognizer is less sure of don't count towards the live WPM">
+                  { format!("Min. confidence {:.2} ", settings.min_confidence) }
+                  <input
+                    type="range"
+                    min="0"
+                    max="1"
+                    step="0.05"
+                    value={settings.min_confidence.to_string()}
+                    oninput={Callback::from({
+                      let settings = settings.clone();
+                      move |e: InputEvent| {
+                        let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                        if let Ok(min_confidence) = v.parse::<f32>() {
+                          settings.set(Settings { min_confidence: min_confidence.clamp(0.0, 1.0), ..(*settings).clone() });
+                        }
+                      }
+                    })}
+                  />
+                </label>
+              }
+              <span class="font-size-control">
+                { "Prompter text " }
+                <button
+                  onclick={Callback::from({
+                    let settings = settings.clone();
+                    move |_| {
+                      let font_size = (settings.font_size - 2).max(PROMPTER_FONT_RANGE.0);
+                      settings.set(Settings { font_size, ..(*settings).clone() });
+                    }
+                  })}
+                  disabled={settings.font_size <= PROMPTER_FONT_RANGE.0}
+                >{"A−"}</button>
+                { format!(" {}px ", settings.font_size) }
+                <button
+                  onclick={Callback::from({
+                    let settings = settings.clone();
+                    move |_| {
+                      let font_size = (settings.font_size + 2).min(PROMPTER_FONT_RANGE.1);
+                      settings.set(Settings { font_size, ..(*settings).clone() });
+                    }
+                  })}
+                  disabled={settings.font_size >= PROMPTER_FONT_RANGE.1}
+                >{"A+"}</button>
+              </span>
+              <label title="How much of the script the prompter shows at once">
+                { "Lines shown " }
+                <input
+                  type="number"
+                  class="prompter-lines-input"
+                  min={PROMPTER_LINES_RANGE.0.to_string()}
+                  max={PROMPTER_LINES_RANGE.1.to_string()}
+                  value={settings.prompter_lines.to_string()}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |e: Event| {
+                      let input = e.target_unchecked_into::<web_sys::HtmlInputElement>();
+                      match input.value().trim().parse::<u32>() {
+                        Ok(v) => {
+                          let prompter_lines = v.clamp(PROMPTER_LINES_RANGE.0, PROMPTER_LINES_RANGE.1);
+                          input.set_value(&prompter_lines.to_string());
+                          settings.set(Settings { prompter_lines, ..(*settings).clone() });
+                        }
+                        Err(_) => input.set_value(&settings.prompter_lines.to_string()),
+                      }
+                    }
+                  })}
+                />
+              </label>
+              <label>
+                <input
+                  type="checkbox"
+                  checked={settings.mirror_prompter}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |_| settings.set(Settings { mirror_prompter: !settings.mirror_prompter, ..(*settings).clone() })
+                  })}
+                />
+                { " Mirror prompter text" }
+              </label>
+              <label>
+                { "Prompter colours " }
+                <select onchange={Callback::from({
+                  let settings = settings.clone();
+                  move |e: Event| {
+                    let index = e.target_unchecked_into::<web_sys::HtmlSelectElement>().selected_index();
+                    let prompter_theme = PrompterTheme::ALL.get(index as usize).copied().unwrap_or_default();
+                    settings.set(Settings { prompter_theme, ..(*settings).clone() });
+                  }
+                })}>
+                  { for PrompterTheme::ALL.iter().map(|theme| html! {
+                    <option selected={settings.prompter_theme == *theme}>{ theme.label() }</option>
+                  }) }
+                </select>
+              </label>
+              <label>
+                <input
+                  type="checkbox"
+                  checked={settings.mirror_preview}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |_| settings.set(Settings { mirror_preview: !settings.mirror_preview, ..(*settings).clone() })
+                  })}
+                />
+                { " Mirror preview only" }
+              </label>
+              <label>
+                <input
+                  type="checkbox"
+                  checked={audio_only}
+                  disabled={!matches!(*status, RecordingStatus::Idle)}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    // a picked video format means nothing for an audio take, and vice versa
+                    move |_| settings.set(Settings { audio_only: !settings.audio_only, mime_choice: None, ..(*settings).clone() })
+                  })}
+                />
+                { " Audio only" }
+              </label>
+              <label>
+                { "Lead-in (s) " }
+                <input
+                  type="number"
+                  min="0"
+                  max={LEAD_IN_MAX_SECS.to_string()}
+                  value={settings.lead_in_secs.to_string()}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |e: Event| {
+                      let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                      if let Ok(lead_in_secs) = v.trim().parse::<u32>() {
+                        let lead_in_secs = lead_in_secs.min(LEAD_IN_MAX_SECS);
+                        settings.set(Settings { lead_in_secs, ..(*settings).clone() });
+                      }
+                    }
+                  })}
+                />
+              </label>
+              <label>
+                { "Max length (s) " }
+                <input
+                  type="number"
+                  min="1"
+                  placeholder="unlimited"
+                  value={settings.max_duration_secs.map(|secs| secs.to_string()).unwrap_or_default()}
+                  disabled={!matches!(*status, RecordingStatus::Idle)}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |e: Event| {
+                      let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                      let max_duration_secs = v.trim().parse::<u32>().ok().filter(|secs| *secs > 0);
+                      settings.set(Settings { max_duration_secs, ..(*settings).clone() });
+                    }
+                  })}
+                />
+                <button
+                  disabled={settings.max_duration_secs.is_none() || !matches!(*status, RecordingStatus::Idle)}
+                  onclick={Callback::from({
+                    let settings = settings.clone();
+                    move |_| settings.set(Settings { max_duration_secs: None, ..(*settings).clone() })
+                  })}
+                >{"Clear"}</button>
+              </label>
+              <label title="Finish the take and carry on in a new part after this long; each part is its own file">
+                { "Split every (s) " }
+                <input
+                  type="number"
+                  min="1"
+                  placeholder="never"
+                  value={settings.split_every_secs.map(|secs| secs.to_string()).unwrap_or_default()}
+                  disabled={!matches!(*status, RecordingStatus::Idle)}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |e: Event| {
+                      let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                      let split_every_secs = v.trim().parse::<u32>().ok().filter(|secs| *secs > 0);
+                      settings.set(Settings { split_every_secs, ..(*settings).clone() });
+                    }
+                  })}
+                />
+              </label>
+              <label title="Finish the take and carry on in a new part once it reaches this size; checked about once a second">
+                { "Split at (MB) " }
+                <input
+                  type="number"
+                  min="1"
+                  placeholder="never"
+                  value={settings.split_at_mb.map(|mb| mb.to_string()).unwrap_or_default()}
+                  disabled={!matches!(*status, RecordingStatus::Idle)}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |e: Event| {
+                      let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                      let split_at_mb = v.trim().parse::<u32>().ok().filter(|mb| *mb > 0);
+                      settings.set(Settings { split_at_mb, ..(*settings).clone() });
+                    }
+                  })}
+                />
+              </label>
+              <label>
+                { "Format " }
+                <select
+                  disabled={!matches!(*status, RecordingStatus::Idle)}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |e: Event| {
+                      let value = e.target_unchecked_into::<web_sys::HtmlSelectElement>().value();
+                      let mime_choice = (!value.is_empty()).then_some(value);
+                      settings.set(Settings { mime_choice, ..(*settings).clone() });
+                    }
+                  })}
+                >
+                  <option value="" selected={settings.mime_choice.is_none()}>
+                    { format!("Auto ({})", recording_type.as_deref().unwrap_or("browser default")) }
+                  </option>
+                  { for supported_types.iter().map(|t| html! {
+                    <option value={*t} selected={settings.mime_choice.as_deref() == Some(*t)}>{ *t }</option>
+                  }) }
+                </select>
+              </label>
+              <details class="format-support">
+                <summary>{"What this browser can record"}</summary>
+                <ul>
+                  { for formats.probed.iter().map(|(t, ok)| html! {
+                    <li class={if *ok { "self-test-pass" } else { "self-test-fail" }}>
+                      { format!("{} {}", if *ok { "✓" } else { "✗" }, t) }
+                    </li>
+                  }) }
+                </ul>
+                <p>
+                  { format!(
+                      "Best available: {}",
+                      formats.best(*prefer_hw_encoding, audio_only).unwrap_or("none of these; the browser picks")
+                  ) }
+                </p>
+              </details>
+              <label>
+                { "Quality " }
+                <select
+                  disabled={!matches!(*status, RecordingStatus::Idle)}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |e: Event| {
+                      let index = e.target_unchecked_into::<web_sys::HtmlSelectElement>().selected_index();
+                      let bitrate = BitratePreset::ALL.get(index as usize).copied().unwrap_or_default();
+                      settings.set(Settings { bitrate, ..(*settings).clone() });
+                    }
+                  })}
+                >
+                  { for BitratePreset::ALL.iter().map(|preset| html! {
+                    <option selected={settings.bitrate == *preset}>
+                      { match preset.mb_per_minute(audio_only) {
+                        Some(mb) => format!("{preset} (~{mb:.1} MB/min)"),
+                        None => format!("{preset} (browser default)"),
+                      } }
+                    </option>
+                  }) }
+                </select>
+              </label>
+              <label title="Kept takes come back after a reload. Recordings use browser storage; file paths only cover takes saved to disk">
+                { "Takes " }
+                <select
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    let storage_choices = storage_choices.clone();
+                    move |e: Event| {
+                      let index = e.target_unchecked_into::<web_sys::HtmlSelectElement>().selected_index();
+                      let take_storage = storage_choices.get(index as usize).copied().unwrap_or_default();
+                      settings.set(Settings { take_storage, ..(*settings).clone() });
+                    }
+                  })}
+                >
+                  { for storage_choices.iter().map(|choice| html! {
+                    <option selected={settings.take_storage == *choice}>{ choice.to_string() }</option>
+                  }) }
+                </select>
+              </label>
+              <label>
+                { "Upload to " }
+                <input
+                  type="url"
+                  placeholder="https://example.com/upload"
+                  value={settings.upload_url.clone()}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |e: Event| {
+                      let upload_url = e.target_unchecked_into::<web_sys::HtmlInputElement>().value().trim().to_string();
+                      settings.set(Settings { upload_url, ..(*settings).clone() });
+                    }
+                  })}
+                />
+              </label>
+              <label title="Sent as the Authorization header, e.g. \"Bearer …\"; not saved">
+                { "Auth header " }
+                <input
+                  type="password"
+                  autocomplete="off"
+                  value={(*upload_auth).clone()}
+                  disabled={settings.upload_url.is_empty()}
+                  onchange={Callback::from({
+                    let upload_auth = upload_auth.clone();
+                    move |e: Event| {
+                      upload_auth.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value().trim().to_string());
+                    }
+                  })}
+                />
+              </label>
+              <span class="session-file">
+                <button onclick={Callback::from({
+                  let settings = settings.clone();
+                  let script = script.clone();
+                  move |_| {
+                    let session = SessionFile { settings: (*settings).clone(), script: (*script).clone() };
+                    if let Some(json) = session_json(&session) {
+                      download_text(&json, "application/json");
+                    }
+                  }
+                })}>{"Export session"}</button>
+                <label title="Settings and script from an exported session; replaces the current ones">
+                  { "Import session " }
+                  <input
+                    type="file"
+                    accept=".json,application/json"
+                    disabled={!idle}
+                    onchange={Callback::from({
+                      let settings = settings.clone();
+                      let script = script.clone();
+                      let session_notice = session_notice.clone();
+                      let devices_restored = devices_restored.clone();
+                      let device_changes = device_changes.clone();
+                      move |e: Event| {
+                        let input = e.target_unchecked_into::<web_sys::HtmlInputElement>();
+                        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                          return;
+                        };
+                        // picking the same file again should import it again
+                        input.set_value("");
+                        let settings = settings.clone();
+                        let script = script.clone();
+                        let session_notice = session_notice.clone();
+                        let devices_restored = devices_restored.clone();
+                        let device_changes = device_changes.clone();
+                        spawn_local(async move {
+                          let text = wasm_bindgen_futures::JsFuture::from(file.text()).await;
+                          let session = text
+                            .map_err(|err| format!("couldn't read {}: {}", file.name(), js_error_message(&err)))
+                            .and_then(|text| parse_session(&text.as_string().unwrap_or_default()));
+                          match session {
+                            Ok(session) => {
+                              save_script(&session.script);
+                              script.set(session.script);
+                              settings.set(session.settings);
+                              // pick up its camera and mic, where they're plugged in here
+                              *devices_restored.borrow_mut() = false;
+                              device_changes.set(*device_changes + 1);
+                              session_notice.set(Some(Ok(format!("Loaded {}", file.name()))));
+                            }
+                            Err(msg) => session_notice.set(Some(Err(msg))),
+                          }
+                        });
+                      }
+                    })}
+                  />
+                </label>
+                {
+                  match &*session_notice {
+                    Some(Ok(msg)) => html! { <small class="self-test-pass">{ format!("✓ {msg}") }</small> },
+                    Some(Err(msg)) => html! { <small class="self-test-fail">{ format!("✗ Import failed: {msg}") }</small> },
+                    None => html! {},
+                  }
+                }
+              </span>
+            </details>
+            <p>
+              if *speech_supported {
+                { format!("Live WPM: {} ", *live_wpm) }
+                <label>
+                  <input
+                    type="checkbox"
+                    checked={*auto_pace}
+                    onchange={Callback::from({
+                      let auto_pace = auto_pace.clone();
+                      move |_| auto_pace.set(!*auto_pace)
+                    })}
+                  />
+                  {" Auto-pace "}
+                </label>
+              } else {
+                <span class="speech-unsupported">{"Speech recognition is unsupported in this browser. "}</span>
+              }
+              // while auto-pace drives the prompter the slider and box just follow the recognizer
+              <label>
+                { format!("{} ", if *speech_supported { "Scroll WPM" } else { "WPM (manual)" }) }
+                <input
+                  type="number"
+                  class="wpm-input"
+                  min={MANUAL_WPM_RANGE.0.to_string()}
+                  max={MANUAL_WPM_RANGE.1.to_string()}
+                  value={scroll_wpm.borrow().to_string()}
+                  disabled={*auto_pace && *speech_supported}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |e: Event| {
+                      let input = e.target_unchecked_into::<web_sys::HtmlInputElement>();
+                      match input.value().trim().parse::<u32>() {
+                        Ok(v) => {
+                          let target_wpm = v.clamp(MANUAL_WPM_RANGE.0, MANUAL_WPM_RANGE.1);
+                          // show the clamped value even when it's what was already saved
+                          input.set_value(&target_wpm.to_string());
+                          settings.set(Settings { target_wpm, ..(*settings).clone() });
+                        }
+                        Err(_) => input.set_value(&settings.target_wpm.to_string()),
+                      }
+                    }
+                  })}
+                />
+                <input
+                  type="range"
+                  min={MANUAL_WPM_RANGE.0.to_string()}
+                  max={MANUAL_WPM_RANGE.1.to_string()}
+                  value={scroll_wpm.borrow().to_string()}
+                  disabled={*auto_pace && *speech_supported}
+                  oninput={Callback::from({
+                    let settings = settings.clone();
+                    move |e: InputEvent| {
+                      let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                      if let Ok(target_wpm) = v.parse::<u32>() {
+                        settings.set(Settings { target_wpm, ..(*settings).clone() });
+                      }
+                    }
+                  })}
+                />
+              </label>
+              // the WPM above stays the real pace; this only changes how fast the text moves
+              <label>
+                { format!(" Scroll speed: {:.1}× ", settings.scroll_speed) }
+                <input
+                  type="range"
+                  min={SCROLL_SPEED_RANGE.0.to_string()}
+                  max={SCROLL_SPEED_RANGE.1.to_string()}
+                  step="0.1"
+                  value={settings.scroll_speed.to_string()}
+                  oninput={Callback::from({
+                    let settings = settings.clone();
+                    move |e: InputEvent| {
+                      let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                      if let Ok(scroll_speed) = v.parse::<f64>() {
+                        let scroll_speed = scroll_speed.clamp(SCROLL_SPEED_RANGE.0, SCROLL_SPEED_RANGE.1);
+                        settings.set(Settings { scroll_speed, ..(*settings).clone() });
+                      }
+                    }
+                  })}
+                />
+              </label>
+              <label title="Off moves the highlight straight to the next word, for less motion">
+                <input
+                  type="checkbox"
+                  checked={settings.animate_highlight}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |_| settings.set(Settings { animate_highlight: !settings.animate_highlight, ..(*settings).clone() })
+                  })}
+                />
+                { " Slide highlight" }
+              </label>
+              <label title="Jump a line at a time, after the pace has got through the words on it">
+                <input
+                  type="checkbox"
+                  checked={settings.line_by_line}
+                  onchange={Callback::from({
+                    let settings = settings.clone();
+                    move |_| settings.set(Settings { line_by_line: !settings.line_by_line, ..(*settings).clone() })
+                  })}
+                />
+                { " Line by line" }
+              </label>
+            </p>
+            if !transcript.final_text.is_empty() || !transcript.interim.is_empty() {
+              <p class="transcript" aria-live="polite">
+                { text_tail(&transcript.final_text, TRANSCRIPT_TAIL_CHARS) }
+                <span class="interim">{ &transcript.interim }</span>
+              </p>
+              <p>
+                <select onchange={Callback::from({
+                  let transcript_format = transcript_format.clone();
+                  move |e: Event| {
+                    let value = e.target_unchecked_into::<web_sys::HtmlSelectElement>().value();
+                    transcript_format.set(if value == "txt" { TranscriptFormat::Text } else { TranscriptFormat::Srt });
+                  }
+                })}>
+                  <option value="srt" selected={*transcript_format == TranscriptFormat::Srt}>{"Captions (.srt)"}</option>
+                  <option value="txt" selected={*transcript_format == TranscriptFormat::Text}>{"Plain text (.txt)"}</option>
+                </select>
+                <button
+                  disabled={transcript.final_text.is_empty()}
+                  onclick={Callback::from({
+                    let captions = captions.clone();
+                    let format = *transcript_format;
+                    move |_| download_transcript(&captions.borrow(), format)
+                  })}
+                >
+                  {"Download transcript"}
+                </button>
+                <button
+                  disabled={transcript.final_text.is_empty()}
+                  onclick={Callback::from({
+                    let text = transcript.final_text.trim().to_string();
+                    let copy_notice = copy_notice.clone();
+                    let copy_notice_timer = copy_notice_timer.clone();
+                    move |_| {
+                      let text = text.clone();
+                      let copy_notice = copy_notice.clone();
+                      let copy_notice_timer = copy_notice_timer.clone();
+                      spawn_local(async move {
+                        copy_notice.set(Some(copy_text(&text).await));
+                        let copy_notice = copy_notice.clone();
+                        copy_notice_timer
+                          .borrow_mut()
+                          .replace(gloo_timers::callback::Timeout::new(COPY_NOTICE_MS, move || copy_notice.set(None)));
+                      });
+                    }
+                  })}
+                >
+                  {"Copy transcript"}
+                </button>
+                {
+                  match &*copy_notice {
+                    Some(Ok(())) => html! { <small class="self-test-pass" role="status">{" Copied!"}</small> },
+                    Some(Err(msg)) => html! { <small class="self-test-fail" role="status">{ format!(" Couldn't copy: {msg}") }</small> },
+                    None => html! {},
+                  }
+                }
+              </p>
+            }
 
             <div style="margin-bottom: 12px; display: flex; gap: 8px;">
             <textarea
               value={(*script).clone()}
               oninput={Callback::from({
                 let script = script.clone();
+                let script_save = script_save.clone();
                 move |e: InputEvent| {
                   let txt = e.target_unchecked_into::<web_sys::HtmlTextAreaElement>().value();
+                  let saved = txt.clone();
+                  // replacing the Timeout cancels the previous pending save
+                  script_save.borrow_mut().replace(gloo_timers::callback::Timeout::new(
+                    SCRIPT_SAVE_DEBOUNCE_MS,
+                    move || save_script(&saved),
+                  ));
                   script.set(txt);
                 }
               })}
               placeholder="Paste your dialog script here…"
               style="flex:1; height: 80px;"
             />
-            <button onclick={onclick_toggle.clone()}>
+            // at whichever pace the prompter would scroll right now
+            <small class="script-stats">
+              { format!("{} words", script_words) }<br />
+              { format!("~{} at {} WPM", format_mm_ss(read_time_secs(script_words, *scroll_wpm.borrow())), *scroll_wpm.borrow()) }
+            </small>
+            <button onclick={onclick_toggle.reform(|_| ())}>
               { if *is_prompting { "Stop Teleprompter" } else { "Start Teleprompter" } }
             </button>
+            <button
+              onclick={onclick_restart_prompter.reform(|_| ())}
+              disabled={!*is_prompting}
+              title="Back to the top, with the lead-in again"
+            >
+              {"Restart prompter"}
+            </button>
+            <button onclick={onclick_fullscreen.reform(|_| ())} title="Esc to leave fullscreen">
+              {"Fullscreen"}
+            </button>
+            <button
+              title="Lines starting with # become section headings"
+              onclick={Callback::from({
+                let script = script.clone();
+                move |_| print_script(&script)
+              })}
+              disabled={script.trim().is_empty()}
+            >
+              {"Export script (PDF)"}
+            </button>
+            <button
+              onclick={Callback::from({
+                let script = script.clone();
+                let script_save = script_save.clone();
+                move |_| {
+                  script_save.borrow_mut().take();
+                  save_script("");
+                  script.set(String::new());
+                }
+              })}
+              disabled={script.is_empty()}
+            >
+              {"Clear script"}
+            </button>
+          </div>
+
+          <div style="margin-bottom: 12px; display: flex; gap: 8px; align-items: center;">
+            <label title={if *speech_supported { "" } else { "Needs speech recognition" }}>
+              <input
+                type="checkbox"
+                checked={following}
+                disabled={!*speech_supported}
+                onchange={Callback::from({
+                  let follow_voice = follow_voice.clone();
+                  move |_| follow_voice.set(!*follow_voice)
+                })}
+              />
+              { " Follow my voice" }
+            </label>
+            <label>
+              <input
+                type="checkbox"
+                checked={*vad_scroll}
+                disabled={following}
+                onchange={Callback::from({
+                  let vad_scroll = vad_scroll.clone();
+                  move |_| vad_scroll.set(!*vad_scroll)
+                })}
+              />
+              { " Scroll only while speaking" }
+            </label>
+            <label>
+              { "Sensitivity " }
+              <input
+                type="range"
+                min="1"
+                max="100"
+                value={vad_sensitivity.to_string()}
+                disabled={!*vad_scroll || following}
+                oninput={Callback::from({
+                  let vad_sensitivity = vad_sensitivity.clone();
+                  move |e: InputEvent| {
+                    let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                    if let Ok(v) = v.parse::<u32>() {
+                      vad_sensitivity.set(v);
+                    }
+                  }
+                })}
+              />
+            </label>
           </div>
         
+          <div style="margin-bottom: 12px; display: flex; gap: 8px; align-items: center;">
+            <label>
+              <input
+                type="checkbox"
+                checked={*overlay_prompter}
+                disabled={*prompter_only}
+                onchange={Callback::from({
+                  let overlay_prompter = overlay_prompter.clone();
+                  move |_| overlay_prompter.set(!*overlay_prompter)
+                })}
+              />
+              { " Overlay prompter on preview" }
+            </label>
+            <label title="Only the preview goes quiet; takes are always recorded with sound">
+              <input
+                type="checkbox"
+                checked={*preview_muted}
+                disabled={audio_only}
+                onchange={Callback::from({
+                  let preview_muted = preview_muted.clone();
+                  move |_| preview_muted.set(!*preview_muted)
+                })}
+              />
+              { " Mute preview" }
+            </label>
+            <label title="Scroll the prompter with the mouse wheel or arrow keys">
+              <input
+                type="checkbox"
+                checked={*pause_on_nudge}
+                onchange={Callback::from({
+                  let pause_on_nudge = pause_on_nudge.clone();
+                  move |_| pause_on_nudge.set(!*pause_on_nudge)
+                })}
+              />
+              { " Hold scrolling after a nudge" }
+            </label>
+            <label>
+              { "Opacity " }
+              <input
+                type="range"
+                min="10"
+                max="100"
+                value={overlay_opacity.to_string()}
+                disabled={!*overlay_prompter}
+                oninput={Callback::from({
+                  let overlay_opacity = overlay_opacity.clone();
+                  move |e: InputEvent| {
+                    let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                    if let Ok(v) = v.parse::<u32>() {
+                      overlay_opacity.set(v);
+                    }
+                  }
+                })}
+              />
+            </label>
+          </div>
+
+          // the prompter sits above the preview, or on top of it in overlay mode
+          <div class="stage">
           <div
           id="teleprompter"
           ref={tele_ref.clone()}
-          style="
-            width: 640px;
-            height: 120px;            /* fixed height for ~8 lines of text */
+          tabindex="0"
+          style={format!("
+            {}
+            box-sizing: border-box;
+            height: {:.0}px;          /* settings.prompter_lines of text, plus padding */
             overflow-y: hidden;       /* hide overflow so we scroll within it */
-            background: rgba(0,0,0,0.8);
             color: white;
-            font-size: 24px;
-            line-height: 1.4;
-            padding: 8px;
+            font-size: {}px;
+            line-height: {};
+            padding: {}px;
             border-radius: 4px;
-          "
+            {}
+            {}
+            {}
+          ", prompter_placement, prompter_height_px, prompter_font_px, PROMPTER_LINE_HEIGHT, PROMPTER_PADDING_PX,
+             if *prompter_fullscreen { "padding: 5vh 8vw;" } else { "" },
+             if settings.mirror_prompter { "transform: scaleX(-1);" } else { "" },
+             settings.prompter_theme.colors().map_or(String::new(), |(text, background, highlight)| {
+               format!("color: {text}; background: {background}; --word-highlight: {highlight};")
+             }))}
         >
-          { for script.split_whitespace().map(|w| html!{<span>{format!("{} ", w)}</span>}) }
+          <div class="word-highlight" ref={highlight_ref.clone()} style="display: none;" />
+          // one block per line as written, so stanza breaks survive; blank lines keep their height
+          { for script_lines(&script).into_iter().map(|line| html! {
+              <div class="script-line">
+                if line.is_empty() {
+                  { "\u{a0}" }
+                }
+                { for line.into_iter().map(|(i, w)| html! {
+                    <span class={classes!((*is_prompting && !settings.animate_highlight && i == *current_word).then_some("current-word"))}>{format!("{} ", w)}</span>
+                }) }
+              </div>
+          }) }
         </div>
-            // 1️⃣ Live webcam preview
-            <video ref={video_ref.clone()} width="640" height="480" autoplay=true playsinline=true />
+          <div class="prompter-progress" title="How far through the script the prompter is">
+            <div class="prompter-progress-fill" ref={progress_bar_ref.clone()} />
+          </div>
+            if let Some(msg) = &*recorder_error {
+                <div class="media-error" role="alert">
+                    <span>{ msg }</span>
+                    <button onclick={Callback::from({
+                      let recorder_error = recorder_error.clone();
+                      move |_| recorder_error.set(None)
+                    })}>{"Dismiss"}</button>
+                </div>
+            }
+            if *take_split_notice {
+                <div class="media-notice" role="status">
+                    <span>{ "Switching cameras ended the take: what was recorded so far is kept as its own take, and recording carries on in a new one." }</span>
+                    <button onclick={Callback::from({
+                      let take_split_notice = take_split_notice.clone();
+                      move |_| take_split_notice.set(false)
+                    })}>{"Dismiss"}</button>
+                </div>
+            }
+            if !*prompter_only {
+            {
+                if let Some(err) = &*media_error {
+                    html! {
+                        <div class="media-error" role="alert">
+                            <span>{ err.to_string() }</span>
+                            <button onclick={Callback::from({
+                              let media_retries = media_retries.clone();
+                              move |_| media_retries.set(*media_retries + 1)
+                            })}>{"Retry"}</button>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            if *devices_listed {
+              <div class="preflight" role="status">
+                { for [
+                    (!audio_only).then(|| preflight_line("Camera", cameras.len(), *camera_access)),
+                    Some(preflight_line("Microphone", mics.len(), *mic_access)),
+                  ].into_iter().flatten().map(|(ok, line)| html! {
+                    <span class={if ok { "self-test-pass" } else { "self-test-fail" }}>{ line }</span>
+                  }) }
+              </div>
+            }
+            <div class="device-pickers" style="margin-bottom: 12px; display: flex; gap: 8px;">
+              <select
+                disabled={audio_only}
+                title={(!idle).then_some("Without an overlay some browsers can only switch by splitting the take in two")}
+                onchange={onchange_camera.reform(|e: Event| {
+                  let id = e.target_unchecked_into::<web_sys::HtmlSelectElement>().value();
+                  (!id.is_empty()).then_some(id)
+                })}
+              >
+                <option value="" selected={capture.video_device.is_none()}>{"Default camera"}</option>
+                { for cameras.iter().map(|d| html! {
+                  <option value={d.id.clone()} selected={capture.video_device.as_ref() == Some(&d.id)}>{ d.label.clone() }</option>
+                }) }
+              </select>
+              <select
+                title="Asked of the camera, which may pick something close instead"
+                disabled={audio_only || !idle}
+                onchange={Callback::from({
+                  let settings = settings.clone();
+                  move |e: Event| {
+                    let index = e.target_unchecked_into::<web_sys::HtmlSelectElement>().selected_index();
+                    let resolution = Resolution::ALL.get(index as usize).copied().unwrap_or_default();
+                    settings.set(Settings { resolution, ..(*settings).clone() });
+                  }
+                })}
+              >
+                { for Resolution::ALL.iter().map(|r| html! {
+                  <option selected={settings.resolution == *r}>{ r.to_string() }</option>
+                }) }
+              </select>
+              <select
+                title="Asked of the camera; more frames look smoother but make bigger files"
+                disabled={audio_only || !idle}
+                onchange={Callback::from({
+                  let settings = settings.clone();
+                  move |e: Event| {
+                    let index = e.target_unchecked_into::<web_sys::HtmlSelectElement>().selected_index();
+                    let frame_rate = FrameRate::ALL.get(index as usize).copied().unwrap_or_default();
+                    settings.set(Settings { frame_rate, ..(*settings).clone() });
+                  }
+                })}
+              >
+                { for FrameRate::ALL.iter().map(|r| html! {
+                  <option selected={settings.frame_rate == *r}>{ r.to_string() }</option>
+                }) }
+              </select>
+              // what the camera really gave us
+              if let Some((width, height)) = (*stream_handle).as_ref().filter(|_| !audio_only).and_then(video_resolution) {
+                <small class="camera-resolution">{
+                  match (*stream_handle).as_ref().and_then(video_frame_rate) {
+                    Some(fps) => format!("{width}×{height} @ {fps:.0} fps"),
+                    None => format!("{width}×{height}"),
+                  }
+                }</small>
+              }
+              <select
+                disabled={!matches!(*status, RecordingStatus::Idle)}
+                onchange={Callback::from({
+                  let capture = capture.clone();
+                  let settings = settings.clone();
+                  move |e: Event| {
+                    let id = e.target_unchecked_into::<web_sys::HtmlSelectElement>().value();
+                    let audio_device = (!id.is_empty()).then_some(id);
+                    settings.set(Settings { mic_id: audio_device.clone(), ..(*settings).clone() });
+                    capture.set(CaptureConfig { audio_device, ..(*capture).clone() });
+                  }
+                })}
+              >
+                <option value="" selected={capture.audio_device.is_none()}>{"Default microphone"}</option>
+                { for mics.iter().map(|d| html! {
+                  <option value={d.id.clone()} selected={capture.audio_device.as_ref() == Some(&d.id)}>{ d.label.clone() }</option>
+                }) }
+              </select>
+            </div>
+            }
+            // REC / PAUSED badge; absolutely positioned but with no offsets, so it sits
+            // where it would have been in flow — the preview's top-left corner
+            {
+                match *status {
+                    RecordingStatus::Recording => html! {
+                        <div class="rec-badge" aria-live="polite"><span class="rec-dot"></span>{"REC"}</div>
+                    },
+                    RecordingStatus::Paused => html! {
+                        <div class="rec-badge paused" aria-live="polite">{"PAUSED"}</div>
+                    },
+                    RecordingStatus::Idle => html! {},
+                }
+            }
+            if *no_speech && matches!(*status, RecordingStatus::Recording) {
+                <div class="no-speech-hint" role="status">{"No speech detected — check your mic"}</div>
+            }
+            // 1️⃣ Live webcam preview (swapped for the composited canvas when an overlay is on)
+            <video
+              ref={video_ref.clone()}
+              autoplay=true
+              playsinline=true
+              style={if overlay_active || audio_only || *prompter_only { "display: none;" } else { preview_flip }}
+            />
+            <canvas
+              ref={overlay_canvas_ref.clone()}
+              style={if overlay_active && !*prompter_only { preview_flip.to_string() } else { "display: none;".to_string() }}
+            />
+          </div>
+            if analyser_handle.is_some() {
+              <div class="level-meter" title="Microphone level">
+                <div class="level-meter-fill" ref={level_bar_ref.clone()} />
+              </div>
+            }
+            if mic_gain.is_some() {
+              <label class="mic-gain" title="Boosts a quiet mic in the meter and in takes">
+                { format!("Mic gain {:.1}× ", settings.mic_gain) }
+                <input
+                  type="range"
+                  min="0"
+                  max={MIC_GAIN_MAX.to_string()}
+                  step="0.1"
+                  value={settings.mic_gain.to_string()}
+                  oninput={Callback::from({
+                    let settings = settings.clone();
+                    move |e: InputEvent| {
+                      let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                      if let Ok(mic_gain) = v.parse::<f64>() {
+                        settings.set(Settings { mic_gain: mic_gain.clamp(0.0, MIC_GAIN_MAX), ..(*settings).clone() });
+                      }
+                    }
+                  })}
+                />
+              </label>
+            }
+
+            if !*prompter_only {
+            <details class="lower-third-settings">
+              <summary>{"Lower third"}</summary>
+              <label>
+                <input
+                  type="checkbox"
+                  checked={lower_third.enabled}
+                  disabled={!matches!(*status, RecordingStatus::Idle)}
+                  onchange={Callback::from({
+                    let lower_third = lower_third.clone();
+                    move |_| lower_third.set(LowerThird { enabled: !lower_third.enabled, ..(*lower_third).clone() })
+                  })}
+                />
+                { " Show lower third (composited into the recording)" }
+              </label>
+              <input
+                type="text"
+                placeholder="Name"
+                value={lower_third.title.clone()}
+                oninput={Callback::from({
+                  let lower_third = lower_third.clone();
+                  move |e: InputEvent| {
+                    let title = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                    lower_third.set(LowerThird { title, ..(*lower_third).clone() });
+                  }
+                })}
+              />
+              <input
+                type="text"
+                placeholder="Title"
+                value={lower_third.subtitle.clone()}
+                oninput={Callback::from({
+                  let lower_third = lower_third.clone();
+                  move |e: InputEvent| {
+                    let subtitle = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                    lower_third.set(LowerThird { subtitle, ..(*lower_third).clone() });
+                  }
+                })}
+              />
+              <select onchange={Callback::from({
+                let lower_third = lower_third.clone();
+                move |e: Event| {
+                  let font_family = e.target_unchecked_into::<web_sys::HtmlSelectElement>().value();
+                  lower_third.set(LowerThird { font_family, ..(*lower_third).clone() });
+                }
+              })}>
+                { for ["sans-serif", "serif", "monospace"].iter().map(|f| html! {
+                  <option value={*f} selected={lower_third.font_family == *f}>{ *f }</option>
+                }) }
+              </select>
+              <label>
+                { "Size " }
+                <input
+                  type="number"
+                  min="12"
+                  max="96"
+                  value={lower_third.font_px.to_string()}
+                  oninput={Callback::from({
+                    let lower_third = lower_third.clone();
+                    move |e: InputEvent| {
+                      let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                      if let Ok(font_px) = v.parse::<u32>() {
+                        lower_third.set(LowerThird { font_px: font_px.clamp(12, 96), ..(*lower_third).clone() });
+                      }
+                    }
+                  })}
+                />
+              </label>
+              <select onchange={Callback::from({
+                let lower_third = lower_third.clone();
+                move |e: Event| {
+                  let align = match e.target_unchecked_into::<web_sys::HtmlSelectElement>().value().as_str() {
+                    "center" => OverlayAlign::Center,
+                    "right" => OverlayAlign::Right,
+                    _ => OverlayAlign::Left,
+                  };
+                  lower_third.set(LowerThird { align, ..(*lower_third).clone() });
+                }
+              })}>
+                <option value="left" selected={lower_third.align == OverlayAlign::Left}>{"Left"}</option>
+                <option value="center" selected={lower_third.align == OverlayAlign::Center}>{"Center"}</option>
+                <option value="right" selected={lower_third.align == OverlayAlign::Right}>{"Right"}</option>
+              </select>
+              <label>
+                <input
+                  type="checkbox"
+                  checked={lower_third.animate}
+                  onchange={Callback::from({
+                    let lower_third = lower_third.clone();
+                    move |_| lower_third.set(LowerThird { animate: !lower_third.animate, ..(*lower_third).clone() })
+                  })}
+                />
+                { " Animate in at " }
+              </label>
+              <input
+                type="number"
+                min="0"
+                step="0.5"
+                value={lower_third.in_secs.to_string()}
+                disabled={!lower_third.animate}
+                oninput={Callback::from({
+                  let lower_third = lower_third.clone();
+                  move |e: InputEvent| {
+                    let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                    if let Ok(in_secs) = v.parse::<f64>() {
+                      lower_third.set(LowerThird { in_secs: in_secs.max(0.0), ..(*lower_third).clone() });
+                    }
+                  }
+                })}
+              />
+              { "s, out at " }
+              <input
+                type="number"
+                min="0"
+                step="0.5"
+                value={lower_third.out_secs.to_string()}
+                disabled={!lower_third.animate}
+                oninput={Callback::from({
+                  let lower_third = lower_third.clone();
+                  move |e: InputEvent| {
+                    let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                    if let Ok(out_secs) = v.parse::<f64>() {
+                      lower_third.set(LowerThird { out_secs: out_secs.max(0.0), ..(*lower_third).clone() });
+                    }
+                  }
+                })}
+              />
+              { "s (0 = stay on)" }
+            </details>
+
+            <label class="stamp-settings">
+              { "Burn in " }
+              <select
+                disabled={!matches!(*status, RecordingStatus::Idle)}
+                onchange={Callback::from({
+                  let stamp_mode = stamp_mode.clone();
+                  move |e: Event| {
+                    let mode = match e.target_unchecked_into::<web_sys::HtmlSelectElement>().value().as_str() {
+                      "timestamp" => StampMode::Timestamp,
+                      "timecode" => StampMode::Timecode,
+                      "both" => StampMode::Both,
+                      _ => StampMode::None,
+                    };
+                    stamp_mode.set(mode);
+                  }
+                })}
+              >
+                <option value="none" selected={*stamp_mode == StampMode::None}>{"Nothing"}</option>
+                <option value="timestamp" selected={*stamp_mode == StampMode::Timestamp}>{"Timestamp"}</option>
+                <option value="timecode" selected={*stamp_mode == StampMode::Timecode}>{"Timecode"}</option>
+                <option value="both" selected={*stamp_mode == StampMode::Both}>{"Timestamp + timecode"}</option>
+              </select>
+            </label>
 
+            <p class="recording-timer">
+              { format_mm_ss(*elapsed_secs) }
+              if let (Some(limit), false) = (settings.max_duration_secs, matches!(*status, RecordingStatus::Idle)) {
+                <small>{ format!(" ({} left)", format_mm_ss(limit.saturating_sub(session_secs))) }</small>
+              }
+              if segments.borrow().part > 0 {
+                <small>{ format!(" · part {}, {} in all", segments.borrow().part, format_mm_ss(session_secs)) }</small>
+              }
+            </p>
             <div class="controls">
+                <label>
+                  <input
+                    type="checkbox"
+                    checked={*prefer_hw_encoding}
+                    disabled={audio_only || !matches!(*status, RecordingStatus::Idle)}
+                    onchange={Callback::from({
+                      let prefer_hw_encoding = prefer_hw_encoding.clone();
+                      move |_| prefer_hw_encoding.set(!*prefer_hw_encoding)
+                    })}
+                  />
+                  { " Prefer hardware encoding" }
+                </label>
+                <small>{ format!(" {}", *encoder_report) }</small>
+                <button
+                  onclick={onclick_self_test.reform(|_| ())}
+                  disabled={!matches!(*status, RecordingStatus::Idle) || *self_test == Some(SelfTestState::Running)}
+                >
+                  {"Run self-test"}
+                </button>
+                <button
+                  title={format!("Record {MIC_TEST_SECS} seconds and play them back; nothing is kept")}
+                  onclick={onclick_mic_test.reform(|_| ())}
+                  disabled={!matches!(*status, RecordingStatus::Idle) || stream_handle.is_none() || mic_testing}
+                >
+                  {"Test mic"}
+                </button>
+                <button
+                  title="Stop and discard any recording, clear takes and transcript, and start the camera over"
+                  onclick={Callback::from({
+                    let onclick_reset = onclick_reset.clone();
+                    move |_| {
+                      if confirm("Reset everything? Unsaved takes will be lost.") {
+                        onclick_reset.emit(());
+                      }
+                    }
+                  })}
+                >
+                  {"Reset"}
+                </button>
+                {
+                    match &*self_test {
+                        Some(SelfTestState::Running) => html! { <small>{ format!(" Testing ({}s sample)…", SELF_TEST_SECS) }</small> },
+                        Some(SelfTestState::Passed) => html! { <small class="self-test-pass">{" ✓ Self-test passed"}</small> },
+                        Some(SelfTestState::Failed(stage, msg)) => html! {
+                            <small class="self-test-fail">{ format!(" ✗ Self-test failed at {}: {}", stage, msg) }</small>
+                        },
+                        None => html! {},
+                    }
+                }
+                {
+                    match &*mic_test {
+                        Some(MicTestState::Recording) => html! { <small role="status">{" Recording a mic sample… speak now"}</small> },
+                        Some(MicTestState::Playing) => html! { <small role="status">{" Playing it back…"}</small> },
+                        Some(MicTestState::Failed(msg)) => html! { <small class="self-test-fail">{ format!(" ✗ Mic test failed: {msg}") }</small> },
+                        None => html! {},
+                    }
+                }
                 <p>{ format!("Status: {}", *status) }</p>
-                <button onclick={onclick_start.clone()} disabled={!matches!(*status, RecordingStatus::Idle)}>{"Record"}</button>
-                <button onclick={onclick_pause.clone()} disabled={!matches!(*status, RecordingStatus::Recording)}>{"Pause"}</button>
-                <button onclick={onclick_stop.clone()} disabled={matches!(*status, RecordingStatus::Recording)}>{"Stop & Preview"}</button>
+                <button
+                  onclick={onclick_start.reform(|_| ())}
+                  disabled={!matches!(*status, RecordingStatus::Idle) || media_error.is_some() || mic_testing}
+                  title={if *start_pending { "Click again to cancel" } else { "" }}
+                >
+                  {
+                      match (recorder_handle.is_some(), *start_pending) {
+                          (true, _) => "Record",
+                          (false, false) => "Record (initializing camera…)",
+                          (false, true) => "Starting when the camera is ready…",
+                      }
+                  }
+                </button>
+                <button onclick={onclick_pause.reform(|_| ())} disabled={!matches!(*status, RecordingStatus::Recording)}>{"Pause"}</button>
+                <button onclick={onclick_resume.reform(|_| ())} disabled={!matches!(*status, RecordingStatus::Paused)}>{"Resume"}</button>
+                <button onclick={onclick_stop.reform(|_| ())} disabled={idle}>{"Stop & Preview"}</button>
+            </div>
+            }
+            <div class="session-timer">
+                <label>
+                  <input
+                    type="checkbox"
+                    checked={*break_reminder_on}
+                    onchange={Callback::from({
+                      let break_reminder_on = break_reminder_on.clone();
+                      let break_due = break_due.clone();
+                      move |_| {
+                        break_reminder_on.set(!*break_reminder_on);
+                        break_due.set(false);
+                      }
+                    })}
+                  />
+                  { " Remind me to take a break every " }
+                </label>
+                <input
+                  type="number"
+                  min="1"
+                  max="240"
+                  value={break_interval_mins.to_string()}
+                  oninput={Callback::from({
+                    let break_interval_mins = break_interval_mins.clone();
+                    move |e: InputEvent| {
+                      let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                      if let Ok(mins) = v.parse::<u32>() {
+                        break_interval_mins.set(mins.clamp(1, 240));
+                      }
+                    }
+                  })}
+                />
+                { " min" }
             </div>
-            // 2️⃣ Playback of the recorded video
             {
-                if let Some(url) = &*playback_url {
+                if *break_due {
                     html! {
-                        <video src={url.clone()} width="640" height="480" controls=true />
+                        <div class="break-reminder" role="status">
+                            { format!("You've been at it for {} min — time for a short break.", *break_interval_mins) }
+                            <button onclick={Callback::from({
+                              let break_due = break_due.clone();
+                              move |_| break_due.set(false)
+                            })}>{"Dismiss"}</button>
+                        </div>
                     }
                 } else {
                     html! {}
                 }
             }
+            // 2️⃣ Playback of every take so far
+            if !takes.is_empty() {
+                <section class="takes">
+                    if takes.iter().map(|t| t.blob.size()).sum::<f64>() > TAKES_WARN_BYTES {
+                        <p class="takes-warning" role="status">
+                            { format!("{} takes are held in memory; save the keepers and delete the rest.", takes.len()) }
+                        </p>
+                    }
+                    if let Some((used, allowed)) = storage_warning {
+                        <p class="takes-warning" role="status">
+                            { format!(
+                                "Kept takes are using {:.0} of the {:.0} MB this browser allows; delete some{}.",
+                                used / (1024.0 * 1024.0),
+                                allowed / (1024.0 * 1024.0),
+                                if in_tauri() { ", or keep saved takes by file path only" } else { "" },
+                            ) }
+                        </p>
+                    }
+                    if let Some(err) = &*take_store_error {
+                        <p class="takes-warning" role="alert">{ format!("Couldn't keep the takes for next time: {}", err) }</p>
+                    }
+                    <button onclick={onclick_retake.reform(|_| ())}>{"Discard last & Retake"}</button>
+                    <span class="take-filters">
+                      <label>
+                        <input
+                          type="checkbox"
+                          checked={*best_first}
+                          onchange={Callback::from({
+                            let best_first = best_first.clone();
+                            move |_| best_first.set(!*best_first)
+                          })}
+                        />
+                        { " Best first" }
+                      </label>
+                      <select
+                        title="Hide takes rated below this"
+                        onchange={Callback::from({
+                          let min_rating = min_rating.clone();
+                          move |e: Event| {
+                            let index = e.target_unchecked_into::<web_sys::HtmlSelectElement>().selected_index();
+                            min_rating.set(index.clamp(0, 5) as u8);
+                          }
+                        })}
+                      >
+                        <option selected={*min_rating == 0}>{"All takes"}</option>
+                        { for (1..=5u8).map(|stars| html! {
+                          <option selected={*min_rating == stars}>{ format!("{}+ stars", stars) }</option>
+                        }) }
+                      </select>
+                    </span>
+                    <span class="playback-rates" role="group" aria-label="Playback speed">
+                      { for [0.5, 1.0, 1.5, 2.0].into_iter().map(|rate| html! {
+                        <button
+                          class={classes!((*playback_rate == rate).then_some("selected"))}
+                          aria-pressed={(*playback_rate == rate).to_string()}
+                          onclick={Callback::from({
+                            let playback_rate = playback_rate.clone();
+                            move |_| playback_rate.set(rate)
+                          })}
+                        >
+                          { format!("{rate}×") }
+                        </button>
+                      }) }
+                    </span>
+                    if in_tauri() {
+                      <label>
+                        <input
+                          type="checkbox"
+                          checked={*ask_before_overwrite}
+                          onchange={Callback::from({
+                            let ask_before_overwrite = ask_before_overwrite.clone();
+                            move |_| ask_before_overwrite.set(!*ask_before_overwrite)
+                          })}
+                        />
+                        { " Ask before overwriting" }
+                      </label>
+                    }
+                    <div
+                      class="take-list"
+                      title="Focus a take and press 1–5 to rate it, 0 to clear"
+                      onkeydown={Callback::from({
+                        let selected_take = selected_take.clone();
+                        let update_take = update_take.clone();
+                        move |e: KeyboardEvent| {
+                          // digits typed into the trim points aren't ratings
+                          let typing = e
+                            .target()
+                            .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+                            .is_some_and(|el| matches!(el.tag_name().as_str(), "TEXTAREA" | "INPUT" | "SELECT"));
+                          if typing || e.ctrl_key() || e.meta_key() || e.alt_key() {
+                            return;
+                          }
+                          let (Some(number), Ok(rating)) = (*selected_take, e.key().parse::<u8>()) else {
+                            return;
+                          };
+                          if rating <= 5 {
+                            e.prevent_default();
+                            update_take(number, &|t| t.rating = rating);
+                          }
+                        }
+                      })}
+                    >
+                    if hidden_takes > 0 {
+                      <small class="takes-hidden">{ format!("{} take{} rated lower hidden", hidden_takes, if hidden_takes == 1 { "" } else { "s" }) }</small>
+                    }
+                    { for shown_takes.into_iter().map(|take| {
+                        let desktop = in_tauri();
+                        let onclick_download = {
+                            let take = take.clone();
+                            let update_take = update_take.clone();
+                            let ask_before_overwrite = *ask_before_overwrite;
+                            Callback::from(move |_| {
+                                let filename = take.filename();
+                                let number = take.number;
+                                let blob = match take.export_blob() {
+                                    Ok(blob) => blob,
+                                    Err(err) => {
+                                        let msg = format!("couldn't trim the take: {}", js_error_message(&err));
+                                        update_take(number, &|t| t.save_status = Some(SaveStatus::Failed(msg.clone())));
+                                        return;
+                                    }
+                                };
+                                if desktop {
+                                    let update_take = update_take.clone();
+                                    update_take(number, &|t| t.save_status = Some(SaveStatus::Saving));
+                                    spawn_local(async move {
+                                        let status = match save_recording(blob, filename, ask_before_overwrite).await {
+                                            Ok(Some(path)) => Some(SaveStatus::Saved(path)),
+                                            Ok(None) => None,
+                                            Err(err) => Some(SaveStatus::Failed(err)),
+                                        };
+                                        update_take(number, &|t| t.save_status = status.clone());
+                                    });
+                                } else if take.is_trimmed() {
+                                    download_blob(&blob, &filename);
+                                } else {
+                                    trigger_download(&take.url, &filename);
+                                }
+                            })
+                        };
+                        html! {
+                            <div
+                              class={classes!("take", (*selected_take == Some(take.number)).then_some("selected"))}
+                              key={take.number}
+                              tabindex="0"
+                              onfocusin={Callback::from({
+                                let selected_take = selected_take.clone();
+                                let number = take.number;
+                                move |_: FocusEvent| selected_take.set(Some(number))
+                              })}
+                            >
+                                <strong>
+                                  { format!("Take {}", take.number) }
+                                  if let Some(part) = take.segment {
+                                    { format!(", part {}", part) }
+                                  }
+                                  { format!(" ({})", format_mm_ss(take.duration_secs.round() as u32)) }
+                                </strong>
+                                <span class="take-rating" role="group" aria-label="Rating">
+                                  { for (1..=5u8).map(|stars| html! {
+                                    <button
+                                      class={classes!((take.rating >= stars).then_some("lit"))}
+                                      aria-label={format!("{} star{}", stars, if stars == 1 { "" } else { "s" })}
+                                      aria-pressed={(take.rating == stars).to_string()}
+                                      onclick={Callback::from({
+                                        let update_take = update_take.clone();
+                                        let number = take.number;
+                                        // clicking the current rating again clears it
+                                        let rating = if take.rating == stars { 0 } else { stars };
+                                        move |_| update_take(number, &|t| t.rating = rating)
+                                      })}
+                                    >
+                                      { if take.rating >= stars { "★" } else { "☆" } }
+                                    </button>
+                                  }) }
+                                </span>
+                                if take.mime.starts_with("audio/") {
+                                    <audio ref={take.player.clone()} src={take.url.clone()} controls=true />
+                                } else {
+                                    <video ref={take.player.clone()} class="take-video" src={take.url.clone()} controls=true />
+                                }
+                                if take.wpm_samples.len() > 1 {
+                                  <span class="pace-sparkline" title="Live WPM over the take">
+                                    <svg width="160" height="32" viewBox="0 0 160 32">
+                                      <polyline points={sparkline_points(&take.wpm_samples, 160.0, 32.0)} />
+                                    </svg>
+                                    <button onclick={Callback::from({
+                                      let samples = take.wpm_samples.clone();
+                                      move |_| download_text(&pace_csv(&samples), "text/csv")
+                                    })}>{"Pace CSV"}</button>
+                                  </span>
+                                }
+                                <label title="Trimming keeps whole one-second chunks; the first second is always kept">
+                                  { "In " }
+                                  <input
+                                    type="number"
+                                    min="0"
+                                    max={take.trim.1.to_string()}
+                                    value={take.trim.0.to_string()}
+                                    onchange={Callback::from({
+                                      let update_take = update_take.clone();
+                                      let (number, out) = (take.number, take.trim.1);
+                                      move |e: Event| {
+                                        let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                                        if let Ok(secs) = v.trim().parse::<u32>() {
+                                          update_take(number, &|t| t.trim.0 = secs.min(out));
+                                        }
+                                      }
+                                    })}
+                                  />
+                                  { " Out " }
+                                  <input
+                                    type="number"
+                                    min={take.trim.0.to_string()}
+                                    max={(take.duration_secs.ceil() as u32).to_string()}
+                                    value={take.trim.1.to_string()}
+                                    onchange={Callback::from({
+                                      let update_take = update_take.clone();
+                                      let (number, from, end) = (take.number, take.trim.0, take.duration_secs.ceil() as u32);
+                                      move |e: Event| {
+                                        let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                                        if let Ok(secs) = v.trim().parse::<u32>() {
+                                          update_take(number, &|t| t.trim.1 = secs.clamp(from, end));
+                                        }
+                                      }
+                                    })}
+                                  />
+                                  { " s" }
+                                </label>
+                                <button
+                                  onclick={onclick_download}
+                                  disabled={take.save_status == Some(SaveStatus::Saving)}
+                                >
+                                  { match (desktop, take.is_trimmed()) {
+                                      (true, false) => "Save",
+                                      (true, true) => "Save trimmed",
+                                      (false, false) => "Download",
+                                      (false, true) => "Download trimmed",
+                                  } }
+                                </button>
+                                if !settings.upload_url.is_empty() {
+                                  <button
+                                    disabled={matches!(take.upload_status, Some(UploadStatus::Uploading(_)))}
+                                    onclick={Callback::from({
+                                      let take = take.clone();
+                                      let update_take = update_take.clone();
+                                      let endpoint = settings.upload_url.clone();
+                                      let auth = (*upload_auth).clone();
+                                      move |_| {
+                                        let number = take.number;
+                                        let on_status = Callback::from({
+                                          let update_take = update_take.clone();
+                                          move |status: UploadStatus| update_take(number, &|t| t.upload_status = Some(status.clone()))
+                                        });
+                                        let sent = take.export_blob().and_then(|blob| {
+                                          let filename = take.filename();
+                                          let auth = (!auth.is_empty()).then_some(auth.as_str());
+                                          upload_recording(&endpoint, auth, &blob, &filename, on_status)
+                                        });
+                                        if let Err(err) = sent {
+                                          gloo::console::error!("upload failed to start:", err);
+                                          update_take(number, &|t| t.upload_status = Some(UploadStatus::NetworkError));
+                                        }
+                                      }
+                                    })}
+                                  >
+                                    {"Upload"}
+                                  </button>
+                                }
+                                <button onclick={delete_take.reform({
+                                    let number = take.number;
+                                    move |_| number
+                                })}>{"Delete"}</button>
+                                {
+                                    match &take.save_status {
+                                        Some(SaveStatus::Saving) => html! { <small>{" Saving…"}</small> },
+                                        Some(SaveStatus::Saved(path)) => html! { <small class="self-test-pass">{ format!(" Saved to {}", path) }</small> },
+                                        Some(SaveStatus::Failed(err)) => html! { <small class="self-test-fail">{ format!(" Save failed: {}", err) }</small> },
+                                        None => html! {},
+                                    }
+                                }
+                                {
+                                    match &take.upload_status {
+                                        Some(UploadStatus::Uploading(Some(sent))) => html! { <small>{ format!(" Uploading… {:.0}%", sent * 100.0) }</small> },
+                                        Some(UploadStatus::Uploading(None)) => html! { <small>{" Uploading…"}</small> },
+                                        Some(UploadStatus::Uploaded) => html! { <small class="self-test-pass">{" Uploaded"}</small> },
+                                        Some(UploadStatus::Rejected(code, text)) => html! {
+                                            <small class="self-test-fail">{ format!(" Upload refused: {} {}", code, text) }</small>
+                                        },
+                                        Some(UploadStatus::NetworkError) => html! {
+                                            <small class="self-test-fail">{" Upload failed: couldn't reach the server"}</small>
+                                        },
+                                        None => html! {},
+                                    }
+                                }
+                            </div>
+                        }
+                    }) }
+                    </div>
+                </section>
+            }
         </main>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caption(start_ms: f64, end_ms: f64, text: &str) -> Caption {
+        Caption { start_ms, end_ms, text: text.to_string() }
+    }
+
+    fn words(text: &str) -> Vec<String> {
+        text.split_whitespace().map(normalize_word).collect()
+    }
+
+    #[test]
+    fn reading_the_script_word_for_word_follows_it() {
+        let script = words("The quick brown fox jumps");
+        assert_eq!(script_progress(&script, &words("the quick brown")), 3);
+        assert_eq!(script_progress(&script, &words("the quick brown fox jumps")), 5);
+    }
+
+    #[test]
+    fn punctuation_and_case_are_ignored() {
+        let script = words("Hello, World! It's NASA's day.");
+        assert_eq!(script_progress(&script, &words("hello world its nasas")), 4);
+    }
+
+    #[test]
+    fn a_skipped_word_doesnt_stall_the_prompter() {
+        let script = words("one two three four five");
+        assert_eq!(script_progress(&script, &words("one three four")), 4);
+    }
+
+    #[test]
+    fn a_repeated_phrase_is_matched_in_order() {
+        let script = words("we will we will rock you");
+        // the first "we will" doesn't jump ahead to the second
+        assert_eq!(script_progress(&script, &words("we will")), 2);
+        assert_eq!(script_progress(&script, &words("we will we will rock")), 5);
+    }
+
+    #[test]
+    fn words_not_in_the_script_are_ignored() {
+        let script = words("one two three");
+        assert_eq!(script_progress(&script, &words("um one uh two")), 2);
+        assert_eq!(script_progress(&script, &[]), 0);
+    }
+
+    #[test]
+    fn words_past_the_lookahead_dont_move_the_position() {
+        let script = words("a b c d e f g h i j k l");
+        // "l" is further ahead than FOLLOW_LOOKAHEAD, so it's taken for a stray match
+        assert_eq!(script_progress(&script, &words("a l")), 1);
+    }
+
+    #[test]
+    fn palette_matches_letters_in_order() {
+        assert!(fuzzy_score("strec", "Start recording").is_some());
+        assert!(fuzzy_score("START", "Start recording").is_some());
+        assert_eq!(fuzzy_score("xyz", "Start recording"), None);
+    }
+
+    #[test]
+    fn palette_prefers_word_starts_and_runs() {
+        let word_start = fuzzy_score("tp", "Toggle prompter").unwrap();
+        let mid_word = fuzzy_score("tp", "Start prompter").unwrap();
+        assert!(word_start > mid_word);
+        let run = fuzzy_score("pause", "Pause recording").unwrap();
+        let scattered = fuzzy_score("pause", "Pick a subtitle style").unwrap_or(i32::MIN);
+        assert!(run > scattered);
+    }
+
+    #[test]
+    fn srt_times_are_zero_padded() {
+        assert_eq!(format_srt_time(0.0), "00:00:00,000");
+        assert_eq!(format_srt_time(61_005.0), "00:01:01,005");
+        assert_eq!(format_srt_time(3_723_040.0), "01:02:03,040");
+    }
+
+    #[test]
+    fn srt_times_roll_over_into_the_next_unit() {
+        assert_eq!(format_srt_time(59_999.0), "00:00:59,999");
+        assert_eq!(format_srt_time(60_000.0), "00:01:00,000");
+        assert_eq!(format_srt_time(3_599_999.0), "00:59:59,999");
+        assert_eq!(format_srt_time(3_600_000.0), "01:00:00,000");
+    }
+
+    #[test]
+    fn srt_times_round_to_the_nearest_millisecond() {
+        assert_eq!(format_srt_time(1_234.4), "00:00:01,234");
+        assert_eq!(format_srt_time(1_234.5), "00:00:01,235");
+        // float noise just under a boundary lands on it, not a millisecond short
+        assert_eq!(format_srt_time(3_599_999.6), "01:00:00,000");
+        assert_eq!(format_srt_time(-5.0), "00:00:00,000");
+    }
+
+    #[test]
+    fn srt_cues_are_numbered_from_one() {
+        let captions = [caption(0.0, 1_500.0, "Hello there"), caption(2_000.0, 3_250.0, "General Kenobi")];
+        assert_eq!(
+            transcript_file(&captions, TranscriptFormat::Srt),
+            "1\n00:00:00,000 --> 00:00:01,500\nHello there\n\n2\n00:00:02,000 --> 00:00:03,250\nGeneral Kenobi\n\n"
+        );
+        assert_eq!(transcript_file(&captions, TranscriptFormat::Text), "Hello there\nGeneral Kenobi\n");
+    }
+}
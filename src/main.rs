@@ -1,8 +1,40 @@
 mod app;
+mod recorder;
+mod speech;
+mod take_store;
 
 use app::App;
 
+// Swap the (now frozen) UI for a recovery screen that shows the panic message
+fn show_crash_screen(message: &str) {
+    let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+        return;
+    };
+    let Some(body) = document.body() else {
+        return;
+    };
+    let Ok(panel) = document.create_element("main") else {
+        return;
+    };
+    panel.set_class_name("container crash-screen");
+    panel.set_inner_html(
+        "<h1>Something went wrong</h1>\
+         <p>Reload the app to continue, or copy the details below into a bug report.</p>\
+         <pre></pre>\
+         <button onclick=\"location.reload()\">Reload</button>",
+    );
+    if let Ok(Some(pre)) = panel.query_selector("pre") {
+        pre.set_text_content(Some(message));
+    }
+    body.set_inner_html("");
+    let _ = body.append_child(&panel);
+}
+
 fn main() {
-    console_error_panic_hook::set_once();
+    std::panic::set_hook(Box::new(|info| {
+        // keep the full console report, then tell the user too
+        console_error_panic_hook::hook(info);
+        show_crash_screen(&info.to_string());
+    }));
     yew::Renderer::<App>::new().render();
 }
@@ -0,0 +1,1225 @@
+// Everything between the camera and a finished take: choosing a recording format,
+// getting the stream, driving MediaRecorder and merging its chunks, plus the self-test.
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Display};
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{Blob, HtmlVideoElement, MediaRecorder, MediaStream, MediaStreamConstraints, MediaStreamTrack, RecordingState, Url};
+use yew::Callback;
+
+use crate::speech::ActiveClock;
+
+// H.264 is the codec browsers most often hand to a hardware encoder; best first
+pub const HW_PREFERRED_TYPES: &[&str] = &[
+    "video/mp4;codecs=avc1,mp4a.40.2",
+    "video/webm;codecs=h264,opus",
+    "video/mp4",
+];
+// VP8/VP9 are almost always encoded in software; AV1 even more so, and slowly
+pub const SW_TYPES: &[&str] = &[
+    "video/webm;codecs=vp9,opus",
+    "video/webm;codecs=vp8,opus",
+    "video/webm;codecs=av01,opus",
+    "video/webm",
+];
+// for audio-only takes
+pub const AUDIO_TYPES: &[&str] = &[
+    "audio/webm;codecs=opus",
+    "audio/ogg;codecs=opus",
+    "audio/mp4",
+    "audio/webm",
+];
+
+// First recording MIME type `supported` accepts, trying HW-friendly codecs first
+// when asked to; `None` leaves the choice to the browser
+fn pick_recording_type(prefer_hw: bool, audio_only: bool, supported: impl Fn(&str) -> bool) -> Option<&'static str> {
+    if audio_only {
+        return AUDIO_TYPES.iter().copied().find(|t| supported(t));
+    }
+    let order: Vec<&'static str> = if prefer_hw {
+        HW_PREFERRED_TYPES.iter().chain(SW_TYPES).copied().collect()
+    } else {
+        SW_TYPES.iter().chain(HW_PREFERRED_TYPES).copied().collect()
+    };
+    order.into_iter().find(|t| supported(t))
+}
+
+// The user's pick if the browser can record it (and it matches the audio-only
+// setting), otherwise our own best guess
+pub fn resolve_recording_type(
+    choice: Option<&str>,
+    prefer_hw: bool,
+    audio_only: bool,
+    supported: impl Fn(&str) -> bool,
+) -> Option<String> {
+    match choice {
+        Some(mime) if supported(mime) && mime.starts_with("audio/") == audio_only => Some(mime.to_string()),
+        _ => pick_recording_type(prefer_hw, audio_only, supported).map(str::to_string),
+    }
+}
+
+// Which of the formats above this browser can record, asked once up front
+#[derive(Clone, PartialEq)]
+pub struct FormatSupport {
+    // every candidate, video (hardware-friendly first) then audio, and whether it's recordable here
+    pub probed: Vec<(&'static str, bool)>,
+}
+
+impl FormatSupport {
+    pub fn probe() -> FormatSupport {
+        let probed = HW_PREFERRED_TYPES
+            .iter()
+            .chain(SW_TYPES)
+            .chain(AUDIO_TYPES)
+            .map(|t| (*t, MediaRecorder::is_type_supported(t)))
+            .collect();
+        FormatSupport { probed }
+    }
+
+    // anything we never probed counts as unsupported
+    pub fn supports(&self, mime: &str) -> bool {
+        self.probed.iter().any(|(t, ok)| *ok && *t == mime)
+    }
+
+    // the recordable formats for video or audio-only takes, best first
+    pub fn recordable(&self, audio_only: bool) -> Vec<&'static str> {
+        self.probed
+            .iter()
+            .filter(|(t, ok)| *ok && t.starts_with("audio/") == audio_only)
+            .map(|(t, _)| *t)
+            .collect()
+    }
+
+    // what Auto would record with
+    pub fn best(&self, prefer_hw: bool, audio_only: bool) -> Option<&'static str> {
+        pick_recording_type(prefer_hw, audio_only, |t| self.supports(t))
+    }
+}
+
+// "video/webm;codecs=vp9,opus" → "video/webm;codecs=vp9" (MediaCapabilities wants video only)
+fn video_content_type(mime: &str) -> String {
+    match mime.split_once(";codecs=") {
+        Some((container, codecs)) => {
+            format!("{};codecs={}", container, codecs.split(',').next().unwrap_or(codecs))
+        }
+        None => mime.to_string(),
+    }
+}
+
+// What we could find out about the encoder actually in use
+#[derive(Clone, PartialEq)]
+pub enum EncoderReport {
+    Unknown,
+    Hardware,
+    Software,
+    // asked for hardware but got a software-only codec
+    SoftwareFallback,
+}
+
+impl Display for EncoderReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                EncoderReport::Unknown => "Encoder: unknown",
+                EncoderReport::Hardware => "Encoder: hardware (power-efficient)",
+                EncoderReport::Software => "Encoder: software",
+                EncoderReport::SoftwareFallback => "Encoder: software (no hardware-friendly codec available)",
+            }
+        )
+    }
+}
+
+// Best-effort: ask MediaCapabilities whether recording `mime` at this size is power-efficient,
+// which in practice means a hardware encoder
+pub async fn probe_power_efficient(mime: &str, width: u32, height: u32) -> Option<bool> {
+    let navigator = web_sys::window()?.navigator();
+    let video = web_sys::VideoConfiguration::new();
+    video.set_content_type(&video_content_type(mime));
+    video.set_width(width);
+    video.set_height(height);
+    video.set_bitrate(2_500_000.0);
+    video.set_framerate("30");
+    let config = web_sys::MediaEncodingConfiguration::new(web_sys::MediaEncodingType::Record);
+    config.set_video(&video);
+    let promise = js_sys::Reflect::get(&navigator, &"mediaCapabilities".into())
+        .ok()
+        .filter(|caps| !caps.is_undefined())
+        .map(|_| navigator.media_capabilities().encoding_info(&config))?;
+    let info: web_sys::MediaCapabilitiesInfo =
+        wasm_bindgen_futures::JsFuture::from(promise).await.ok()?.unchecked_into();
+    Some(info.supported() && info.power_efficient())
+}
+
+// File extension for a recording's MIME type (codec parameters are ignored)
+fn extension_for_mime(mime: &str) -> &'static str {
+    match mime.split(';').next().unwrap_or("").trim() {
+        "video/mp4" => "mp4",
+        "audio/mp4" => "m4a",
+        "audio/webm" => "weba",
+        "video/x-matroska" => "mkv",
+        "audio/ogg" | "video/ogg" => "ogg",
+        "audio/mpeg" => "mp3",
+        "audio/wav" => "wav",
+        "text/plain" => "txt",
+        "application/x-subrip" => "srt",
+        "text/csv" => "csv",
+        "application/json" => "json",
+        _ => "webm",
+    }
+}
+
+// e.g. `born-edited-20250301-142530.webm`
+pub fn recording_filename(date: &js_sys::Date, mime: &str) -> String {
+    format!(
+        "born-edited-{:04}{:02}{:02}-{:02}{:02}{:02}.{}",
+        date.get_full_year(),
+        date.get_month() + 1,
+        date.get_date(),
+        date.get_hours(),
+        date.get_minutes(),
+        date.get_seconds(),
+        extension_for_mime(mime)
+    )
+}
+
+// `born-edited-….webm` → `born-edited-…-part02.webm`, so a split recording's
+// segments sort in order next to each other
+pub fn segment_filename(filename: &str, part: u32) -> String {
+    match filename.rsplit_once('.') {
+        Some((stem, ext)) => format!("{stem}-part{part:02}.{ext}"),
+        None => format!("{filename}-part{part:02}"),
+    }
+}
+
+// Size/quality trade-off for a take; `Auto` leaves the bitrate to the browser
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum BitratePreset {
+    #[default]
+    Auto,
+    Low,
+    Medium,
+    High,
+}
+
+impl BitratePreset {
+    pub const ALL: [BitratePreset; 4] = [BitratePreset::Auto, BitratePreset::Low, BitratePreset::Medium, BitratePreset::High];
+
+    // (video, audio) bits per second to ask MediaRecorder for
+    pub fn bits_per_second(self) -> Option<(u32, u32)> {
+        match self {
+            BitratePreset::Auto => None,
+            BitratePreset::Low => Some((1_000_000, 64_000)),
+            BitratePreset::Medium => Some((2_500_000, 128_000)),
+            BitratePreset::High => Some((8_000_000, 192_000)),
+        }
+    }
+
+    // Roughly how big a minute of recording comes out, in megabytes
+    pub fn mb_per_minute(self, audio_only: bool) -> Option<f64> {
+        let (video, audio) = self.bits_per_second()?;
+        let bits = if audio_only { audio } else { video + audio };
+        Some(bits as f64 * 60.0 / 8.0 / 1_000_000.0)
+    }
+}
+
+impl Display for BitratePreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BitratePreset::Auto => "Auto",
+            BitratePreset::Low => "Low",
+            BitratePreset::Medium => "Medium",
+            BitratePreset::High => "High",
+        })
+    }
+}
+
+// Ask the recorder for a chunk this often, so a crash mid-take doesn't lose
+// everything. Only the first chunk carries the container header, so chunks must
+// be merged in arrival order and never mixed with another recording's.
+const RECORDER_TIMESLICE_MS: i32 = 1000;
+
+// A take as it comes off the recorder: every chunk merged into one blob
+pub struct Take {
+    pub blob: Blob,
+    // what the recorder reports, else what the chunks say, else what we asked for
+    pub mime: String,
+    // the chunks themselves, each with when it ends (ms into the take, pauses cut out), for trimming
+    pub chunks: Vec<(f64, Blob)>,
+}
+
+// less than this can't hold even a container header, let alone a frame
+const MIN_TAKE_BYTES: f64 = 1024.0;
+
+impl Take {
+    // Stopped before the recorder handed over anything playable (Stop right after Record)
+    pub fn too_short(&self) -> bool {
+        self.chunks.is_empty() || self.blob.size() < MIN_TAKE_BYTES
+    }
+}
+
+// A MediaRecorder plus the chunk buffer its handlers fill. Cloning shares both.
+#[derive(Clone)]
+pub struct Recorder {
+    inner: MediaRecorder,
+    // pushed to on every dataavailable, drained on stop; never rendered, so not state
+    chunks: Rc<RefCell<Vec<(f64, Blob)>>>,
+    // when the take started, on a clock that stands still while it's paused
+    clock: Rc<RefCell<ActiveClock>>,
+    started: Rc<Cell<f64>>,
+}
+
+impl PartialEq for Recorder {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl Recorder {
+    // Record `stream` as `mime` at `bitrate` if the browser accepts them, else with its defaults.
+    // `on_take` gets the merged take after each stop (`None` if merging failed).
+    // `on_error` hears why, if the recorder gives up mid-take; the chunks up to
+    // that point still go to `on_take`.
+    pub fn new(
+        stream: &MediaStream,
+        mime: Option<&str>,
+        bitrate: BitratePreset,
+        on_take: Callback<Option<Take>>,
+        on_error: Callback<String>,
+    ) -> Result<Recorder, JsValue> {
+        let bits = bitrate.bits_per_second();
+        let inner = if mime.is_some() || bits.is_some() {
+            let options = web_sys::MediaRecorderOptions::new();
+            if let Some(mime) = mime {
+                options.set_mime_type(mime);
+            }
+            if let Some((video, audio)) = bits {
+                options.set_video_bits_per_second(video);
+                options.set_audio_bits_per_second(audio);
+            }
+            MediaRecorder::new_with_media_stream_and_media_recorder_options(stream, &options)
+                .or_else(|_| MediaRecorder::new_with_media_stream(stream))?
+        } else {
+            MediaRecorder::new_with_media_stream(stream)?
+        };
+        // the bitrates are only hints; say so when the browser went its own way
+        if let Some((video, _)) = bits {
+            let granted = inner.video_bits_per_second();
+            if stream.get_video_tracks().length() > 0 && granted != video {
+                gloo::console::warn!(format!("asked for {video} bps video, the recorder is using {granted}"));
+            }
+        }
+        let chunks = Rc::new(RefCell::new(Vec::<(f64, Blob)>::new()));
+        let clock = Rc::new(RefCell::new(ActiveClock::default()));
+        let started = Rc::new(Cell::new(0.0));
+
+        // ondataavailable → collect blobs, stamped with how far into the take they end
+        {
+            let chunks = chunks.clone();
+            let clock = clock.clone();
+            let started = started.clone();
+            let on_data = Closure::wrap(Box::new(move |e: web_sys::BlobEvent| {
+                // e.data() is Option<web_sys::Blob>, so just unwrap it
+                if let Some(blob) = e.data() {
+                    let ends_at = clock.borrow().now(js_sys::Date::now()) - started.get();
+                    chunks.borrow_mut().push((ends_at, blob));
+                }
+            }) as Box<dyn FnMut(_)>);
+            inner.set_ondataavailable(Some(on_data.as_ref().unchecked_ref()));
+            on_data.forget();
+        }
+
+        // onstop → every chunk has arrived by now: merge them and hand the take over
+        {
+            let chunks = chunks.clone();
+            let mime = mime.map(str::to_string);
+            let recorder = inner.clone();
+            let on_stop = Closure::wrap(Box::new(move || {
+                let chunks = chunks.take();
+                // Merge blobs into one blob, typed as what the recorder says it produced
+                // (players, Safari's especially, won't touch an untyped blob). That can
+                // differ from what we asked for if the options were rejected.
+                let non_empty = |t: String| (!t.is_empty()).then_some(t);
+                let mime = non_empty(recorder.mime_type())
+                    .or_else(|| chunks.first().and_then(|(_, c)| non_empty(c.type_())))
+                    .or_else(|| mime.clone())
+                    .unwrap_or_default();
+                let take = merge_chunks(chunks.iter().map(|(_, blob)| blob), &mime)
+                    .ok()
+                    .map(|blob| Take { blob, mime, chunks });
+                on_take.emit(take);
+            }) as Box<dyn FnMut()>);
+            inner.set_onstop(Some(on_stop.as_ref().unchecked_ref()));
+            on_stop.forget();
+        }
+
+        // onerror → the encoder failed, storage ran out, a track went away…
+        {
+            let recorder = inner.clone();
+            let on_recorder_error = Closure::wrap(Box::new(move |e: web_sys::Event| {
+                let cause = js_sys::Reflect::get(&e, &JsValue::from_str("error")).unwrap_or(e.into());
+                on_error.emit(js_error_message(&cause));
+                // the recorder should stop by itself, but make sure onstop delivers what there is
+                if recorder.state() != RecordingState::Inactive {
+                    let _ = recorder.stop();
+                }
+            }) as Box<dyn FnMut(_)>);
+            inner.set_onerror(Some(on_recorder_error.as_ref().unchecked_ref()));
+            on_recorder_error.forget();
+        }
+
+        Ok(Recorder { inner, chunks, clock, started })
+    }
+
+    pub fn start(&self) -> Result<(), JsValue> {
+        // the new take's header chunk has to come first
+        self.chunks.borrow_mut().clear();
+        self.inner.start_with_time_slice(RECORDER_TIMESLICE_MS)?;
+        *self.clock.borrow_mut() = ActiveClock::default();
+        self.started.set(js_sys::Date::now());
+        Ok(())
+    }
+
+    pub fn pause(&self) -> Result<(), JsValue> {
+        self.inner.pause()?;
+        self.clock.borrow_mut().pause(js_sys::Date::now());
+        Ok(())
+    }
+
+    pub fn resume(&self) -> Result<(), JsValue> {
+        self.inner.resume()?;
+        self.clock.borrow_mut().resume(js_sys::Date::now());
+        Ok(())
+    }
+
+    // No-op when already stopping (e.g. auto-stop fired while onstop is still pending)
+    pub fn stop(&self) -> Result<(), JsValue> {
+        if self.inner.state() == RecordingState::Inactive {
+            return Ok(());
+        }
+        self.inner.stop()
+    }
+
+    // how much the take holds so far; grows a chunk (about a second) at a time
+    pub fn recorded_bytes(&self) -> f64 {
+        self.chunks.borrow().iter().map(|(_, blob)| blob.size()).sum()
+    }
+
+    // the recorder's own view of its state, which is what the UI should show
+    pub fn status(&self) -> RecordingStatus {
+        self.inner.state().into()
+    }
+}
+
+// One blob out of `chunks`, in order, typed as `mime`
+pub fn merge_chunks<'a>(chunks: impl Iterator<Item = &'a Blob>, mime: &str) -> Result<Blob, JsValue> {
+    let arr = js_sys::Array::new();
+    for blob in chunks {
+        arr.push(blob);
+    }
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime);
+    Blob::new_with_blob_sequence_and_options(&arr, &options)
+}
+
+// A take cut down to the chunks overlapping `from_ms..to_ms`, with no re-encoding.
+// Cuts land on chunk boundaries, so they're only good to a second or so
+// (`RECORDER_TIMESLICE_MS`). The first chunk is always kept because it carries the
+// container header: a late in-point still plays that first second, then jumps ahead.
+pub fn trim_chunks(chunks: &[(f64, Blob)], mime: &str, from_ms: f64, to_ms: f64) -> Result<Blob, JsValue> {
+    let ends: Vec<f64> = chunks.iter().map(|(ends_at, _)| *ends_at).collect();
+    merge_chunks(kept_chunks(&ends, from_ms, to_ms).into_iter().map(|i| &chunks[i].1), mime)
+}
+
+// Indices of the chunks `trim_chunks` keeps, given when each chunk ends: the first,
+// plus every chunk overlapping `from_ms..to_ms`. A chunk that only touches an end
+// of the range is left out; an empty or backwards range keeps just the header.
+fn kept_chunks(ends: &[f64], from_ms: f64, to_ms: f64) -> Vec<usize> {
+    let mut begins_at = 0.0;
+    ends.iter()
+        .enumerate()
+        .filter_map(|(i, ends_at)| {
+            let overlaps = from_ms < to_ms && *ends_at > from_ms && begins_at < to_ms;
+            begins_at = *ends_at;
+            (i == 0 || overlaps).then_some(i)
+        })
+        .collect()
+}
+
+// Whether `blob` decodes to any sound; a file with no audio track doesn't decode at all
+async fn has_audio(blob: &Blob) -> Result<bool, JsValue> {
+    let buffer: js_sys::ArrayBuffer = wasm_bindgen_futures::JsFuture::from(blob.array_buffer()).await?.unchecked_into();
+    let ctx = web_sys::AudioContext::new()?;
+    let decoded = match ctx.decode_audio_data(&buffer) {
+        Ok(promise) => wasm_bindgen_futures::JsFuture::from(promise).await,
+        Err(err) => Err(err),
+    };
+    let _ = ctx.close();
+    Ok(decoded.is_ok_and(|audio| {
+        let audio: web_sys::AudioBuffer = audio.unchecked_into();
+        audio.number_of_channels() > 0 && audio.length() > 0
+    }))
+}
+
+// Stages of the pre-recording self-test, in the order they run
+#[derive(Clone, Copy, PartialEq)]
+pub enum SelfTestStage {
+    AcquireStream,
+    Record,
+    Merge,
+    Audio,
+    PlaybackUrl,
+    Playback,
+}
+
+impl Display for SelfTestStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SelfTestStage::AcquireStream => "camera/mic access",
+                SelfTestStage::Record => "recording",
+                SelfTestStage::Merge => "merging chunks",
+                SelfTestStage::Audio => "audio in the recording",
+                SelfTestStage::PlaybackUrl => "creating playback URL",
+                SelfTestStage::Playback => "playback",
+            }
+        )
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum SelfTestState {
+    Running,
+    Passed,
+    Failed(SelfTestStage, String),
+}
+
+pub const SELF_TEST_SECS: u32 = 2;
+
+#[derive(Clone, PartialEq)]
+pub enum MicTestState {
+    Recording,
+    Playing,
+    Failed(String),
+}
+
+pub const MIC_TEST_SECS: u32 = 3;
+
+// resolves once `name` fires on `target`
+fn next_event(target: &web_sys::EventTarget, name: &'static str) -> wasm_bindgen_futures::JsFuture {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        gloo::events::EventListener::once(target, name, move |_| {
+            let _ = resolve.call0(&JsValue::NULL);
+        })
+        .forget();
+    });
+    wasm_bindgen_futures::JsFuture::from(promise)
+}
+
+async fn sleep_ms(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        gloo_timers::callback::Timeout::new(ms, move || {
+            let _ = resolve.call0(&JsValue::NULL);
+        })
+        .forget();
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+// Exercise the whole chain once with a short fixed-length take: stream → record →
+// merge → object URL → muted playback. Reports the first stage that fails. The take
+// goes through the same `Recorder` (and so the same merge) as a real one.
+pub async fn run_self_test(
+    capture: CaptureConfig,
+    mime: Option<String>,
+    bitrate: BitratePreset,
+) -> Result<(), (SelfTestStage, String)> {
+    let fail = |stage: SelfTestStage| move |err: JsValue| (stage, format!("{:?}", err));
+
+    let stream = request_media_stream(&capture).await.map_err(fail(SelfTestStage::AcquireStream))?;
+    // watched through a muted element like the real preview, which must not mute the take
+    let preview = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.create_element("video").ok())
+        .map(|el| el.unchecked_into::<HtmlVideoElement>());
+    if let Some(preview) = &preview {
+        preview.set_muted(true);
+        preview.set_src_object(Some(&stream));
+        let _ = preview.play();
+    }
+
+    let recorded = async {
+        // the recorder hands the take over from its onstop; this resolves once it has
+        let take = Rc::new(RefCell::new(None::<Take>));
+        let failure = Rc::new(RefCell::new(None::<String>));
+        let mut delivered_resolve = None;
+        let delivered = js_sys::Promise::new(&mut |resolve, _reject| delivered_resolve = Some(resolve));
+        let on_take = {
+            let take = take.clone();
+            Callback::from(move |merged: Option<Take>| {
+                *take.borrow_mut() = merged;
+                if let Some(resolve) = &delivered_resolve {
+                    let _ = resolve.call0(&JsValue::NULL);
+                }
+            })
+        };
+        let on_error = {
+            let failure = failure.clone();
+            Callback::from(move |msg: String| *failure.borrow_mut() = Some(msg))
+        };
+        let recorder =
+            Recorder::new(&stream, mime.as_deref(), bitrate, on_take, on_error).map_err(fail(SelfTestStage::Record))?;
+        recorder.start().map_err(fail(SelfTestStage::Record))?;
+        sleep_ms(SELF_TEST_SECS * 1000).await;
+        recorder.stop().map_err(fail(SelfTestStage::Record))?;
+        let _ = wasm_bindgen_futures::JsFuture::from(delivered).await;
+        if let Some(msg) = failure.take() {
+            return Err((SelfTestStage::Record, msg));
+        }
+        take.take().ok_or_else(|| (SelfTestStage::Merge, "couldn't merge the recorded chunks".to_string()))
+    }
+    .await;
+    if let Some(preview) = &preview {
+        preview.set_src_object(None);
+    }
+    stop_tracks(&stream);
+    let merged = recorded?.blob;
+
+    if merged.size() == 0.0 {
+        return Err((SelfTestStage::Merge, "recorded no data".into()));
+    }
+    if stream.get_audio_tracks().length() > 0 && !has_audio(&merged).await.map_err(fail(SelfTestStage::Audio))? {
+        return Err((SelfTestStage::Audio, "the recording has no sound".into()));
+    }
+
+    let url = Url::create_object_url_with_blob(&merged).map_err(fail(SelfTestStage::PlaybackUrl))?;
+    let played = async {
+        let document = web_sys::window().and_then(|w| w.document()).ok_or_else(|| JsValue::from_str("no document"))?;
+        let video: HtmlVideoElement = document.create_element("video")?.unchecked_into();
+        video.set_muted(true);
+        video.set_src(&url);
+        wasm_bindgen_futures::JsFuture::from(video.play()?).await?;
+        video.pause()?;
+        Ok::<_, JsValue>(())
+    }
+    .await;
+    let _ = Url::revoke_object_url(&url);
+    played.map_err(fail(SelfTestStage::Playback))
+}
+
+// Record a few seconds of `stream`'s sound alone and play it straight back, so the
+// mic can be checked before a take. Nothing is kept: the sample's URL is revoked
+// once playback ends. `on_playback` is called as the recording stops and playback starts.
+pub async fn run_mic_test(stream: &MediaStream, on_playback: impl FnOnce()) -> Result<(), JsValue> {
+    let tracks = js_sys::Array::new();
+    for track in stream.get_audio_tracks().iter() {
+        tracks.push(&track);
+    }
+    if tracks.length() == 0 {
+        return Err("there's no microphone to test".into());
+    }
+    // the same tracks, so nothing is opened or stopped that the real take relies on
+    let audio = MediaStream::new_with_tracks(&tracks)?;
+    let recorder = MediaRecorder::new_with_media_stream(&audio)?;
+    let chunks = Rc::new(RefCell::new(Vec::<Blob>::new()));
+    let on_data = {
+        let chunks = chunks.clone();
+        Closure::wrap(Box::new(move |e: web_sys::BlobEvent| {
+            if let Some(blob) = e.data() {
+                chunks.borrow_mut().push(blob);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    recorder.set_ondataavailable(Some(on_data.as_ref().unchecked_ref()));
+    let stopped = next_event(&recorder, "stop");
+    recorder.start()?;
+    sleep_ms(MIC_TEST_SECS * 1000).await;
+    recorder.stop()?;
+    let _ = stopped.await;
+    recorder.set_ondataavailable(None);
+    drop(on_data);
+    let sample = merge_chunks(chunks.borrow().iter(), &recorder.mime_type())?;
+    if sample.size() == 0.0 {
+        return Err("nothing was recorded".into());
+    }
+
+    let url = Url::create_object_url_with_blob(&sample)?;
+    on_playback();
+    let played = async {
+        let document = web_sys::window().and_then(|w| w.document()).ok_or_else(|| JsValue::from_str("no document"))?;
+        let player: web_sys::HtmlMediaElement = document.create_element("audio")?.unchecked_into();
+        player.set_src(&url);
+        // a sample that won't decode fires error instead of ended
+        let finished = js_sys::Promise::new(&mut |resolve, reject| {
+            gloo::events::EventListener::once(&player, "ended", move |_| {
+                let _ = resolve.call0(&JsValue::NULL);
+            })
+            .forget();
+            gloo::events::EventListener::once(&player, "error", move |_| {
+                let _ = reject.call1(&JsValue::NULL, &JsValue::from_str("the sample wouldn't play"));
+            })
+            .forget();
+        });
+        wasm_bindgen_futures::JsFuture::from(player.play()?).await?;
+        wasm_bindgen_futures::JsFuture::from(finished).await?;
+        Ok::<_, JsValue>(())
+    }
+    .await;
+    let _ = Url::revoke_object_url(&url);
+    played
+}
+
+// Camera resolution to ask for; only ever a preference, the camera has the last word
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Resolution {
+    #[default]
+    Auto,
+    Hd720,
+    Hd1080,
+}
+
+impl Resolution {
+    pub const ALL: [Resolution; 3] = [Resolution::Auto, Resolution::Hd720, Resolution::Hd1080];
+
+    // (width, height) to ask for; `None` takes the camera's default
+    pub fn size(self) -> Option<(i32, i32)> {
+        match self {
+            Resolution::Auto => None,
+            Resolution::Hd720 => Some((1280, 720)),
+            Resolution::Hd1080 => Some((1920, 1080)),
+        }
+    }
+}
+
+impl Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.size() {
+            None => write!(f, "Camera default"),
+            Some((_, height)) => write!(f, "{height}p"),
+        }
+    }
+}
+
+// Camera frame rate to ask for; like the resolution, the camera may settle on another
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum FrameRate {
+    #[default]
+    Auto,
+    Fps24,
+    Fps30,
+    Fps60,
+}
+
+impl FrameRate {
+    pub const ALL: [FrameRate; 4] = [FrameRate::Auto, FrameRate::Fps24, FrameRate::Fps30, FrameRate::Fps60];
+
+    // frames per second to ask for; `None` takes the camera's default
+    pub fn fps(self) -> Option<f64> {
+        match self {
+            FrameRate::Auto => None,
+            FrameRate::Fps24 => Some(24.0),
+            FrameRate::Fps30 => Some(30.0),
+            FrameRate::Fps60 => Some(60.0),
+        }
+    }
+}
+
+impl Display for FrameRate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.fps() {
+            None => write!(f, "Default frame rate"),
+            Some(fps) => write!(f, "{fps} fps"),
+        }
+    }
+}
+
+// What to ask getUserMedia for; `None` devices mean the browser default
+#[derive(Clone, PartialEq, Default)]
+pub struct CaptureConfig {
+    pub video_device: Option<String>,
+    pub audio_device: Option<String>,
+    // voiceover mode: no camera at all
+    pub audio_only: bool,
+    pub resolution: Resolution,
+    pub frame_rate: FrameRate,
+}
+
+// `true`, or `{ deviceId: { exact: id } }` when a specific device is chosen
+fn track_constraint(device_id: Option<&str>) -> JsValue {
+    video_constraint(device_id, Resolution::Auto, FrameRate::Auto)
+}
+
+// `track_constraint`, plus `width`/`height` and `frameRate` as ideals (bare values),
+// so a camera that can't manage them falls back instead of failing getUserMedia
+fn video_constraint(device_id: Option<&str>, resolution: Resolution, frame_rate: FrameRate) -> JsValue {
+    if device_id.is_none() && resolution.size().is_none() && frame_rate.fps().is_none() {
+        return JsValue::TRUE;
+    }
+    let track = web_sys::MediaTrackConstraints::new();
+    if let Some(id) = device_id {
+        let exact = web_sys::ConstrainDomStringParameters::new();
+        exact.set_exact_str(id);
+        track.set_device_id_constrain_dom_string_parameters(&exact);
+    }
+    if let Some((width, height)) = resolution.size() {
+        track.set_width_i32(width);
+        track.set_height_i32(height);
+    }
+    if let Some(fps) = frame_rate.fps() {
+        track.set_frame_rate_f64(fps);
+    }
+    track.into()
+}
+
+// The size the camera actually delivers on `stream`, which may not be what was asked
+pub fn video_resolution(stream: &MediaStream) -> Option<(i32, i32)> {
+    let track: MediaStreamTrack = stream.get_video_tracks().iter().next()?.unchecked_into();
+    let settings = track.get_settings();
+    Some((settings.get_width()?, settings.get_height()?))
+}
+
+// The frame rate the camera actually delivers on `stream`
+pub fn video_frame_rate(stream: &MediaStream) -> Option<f64> {
+    let track: MediaStreamTrack = stream.get_video_tracks().iter().next()?.unchecked_into();
+    track.get_settings().get_frame_rate()
+}
+
+// How a mid-take camera change went
+#[derive(Clone, Copy, PartialEq)]
+pub enum CameraSwap {
+    // the recorder kept running; the take stays one continuous file
+    Live,
+    // the recorder reads this stream's tracks directly, and MediaRecorder gives up
+    // when its stream's track set changes: stop, switch, and record a new take
+    NeedsRestart,
+}
+
+// What to do about a mid-take camera change, once the current track has been asked
+// to switch device in place
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SwapPlan {
+    // the track switched and the recorder can carry on as it is
+    Keep,
+    // open the new camera and put its track in the old one's place
+    ReplaceTrack,
+    // stop the take and record the rest as a new one
+    Restart,
+}
+
+// `switched_in_place`: the old track now reads from the new device. `same_size`: it still
+// delivers the frame size it had. A recorder fed by the compositor's canvas never sees the
+// camera's tracks or size; one reading the stream directly gives up (Firefox's does) when
+// the frame size changes or a track is swapped under it.
+pub fn plan_camera_swap(switched_in_place: bool, same_size: bool, recorder_on_canvas: bool) -> SwapPlan {
+    match (switched_in_place, recorder_on_canvas) {
+        (true, true) => SwapPlan::Keep,
+        (true, false) if same_size => SwapPlan::Keep,
+        (false, true) => SwapPlan::ReplaceTrack,
+        _ => SwapPlan::Restart,
+    }
+}
+
+// Point `stream`'s video at another camera while a recording is running on it.
+// First asks the current track to change device in place, which few browsers honour;
+// failing that, swaps the track itself when `recorder_on_canvas` (the compositor is
+// redrawing the preview, so the recorder never sees this stream's track set).
+pub async fn swap_camera(
+    stream: &MediaStream,
+    device_id: Option<&str>,
+    resolution: Resolution,
+    frame_rate: FrameRate,
+    recorder_on_canvas: bool,
+) -> Result<CameraSwap, JsValue> {
+    let old: Option<MediaStreamTrack> = stream.get_video_tracks().iter().next().map(|t| t.unchecked_into());
+    let size_before = video_resolution(stream);
+    let mut switched_in_place = false;
+    if let (Some(old), Some(id)) = (&old, device_id) {
+        let constraints: web_sys::MediaTrackConstraints = video_constraint(Some(id), resolution, frame_rate).unchecked_into();
+        if let Ok(promise) = old.apply_constraints_with_constraints(&constraints) {
+            let applied = wasm_bindgen_futures::JsFuture::from(promise).await.is_ok();
+            switched_in_place = applied && old.get_settings().get_device_id().as_deref() == Some(id);
+        }
+    }
+    match plan_camera_swap(switched_in_place, video_resolution(stream) == size_before, recorder_on_canvas) {
+        SwapPlan::Keep => return Ok(CameraSwap::Live),
+        SwapPlan::Restart => return Ok(CameraSwap::NeedsRestart),
+        SwapPlan::ReplaceTrack => {}
+    }
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let constraints = MediaStreamConstraints::new();
+    constraints.set_video(&video_constraint(device_id, resolution, frame_rate));
+    let promise = window.navigator().media_devices()?.get_user_media_with_constraints(&constraints)?;
+    let camera: MediaStream = wasm_bindgen_futures::JsFuture::from(promise).await?.unchecked_into();
+    let Some(new) = camera.get_video_tracks().iter().next() else {
+        return Err(JsValue::from_str("the camera gave no video track"));
+    };
+    if let Some(old) = old {
+        stream.remove_track(&old);
+        old.stop();
+    }
+    stream.add_track(&new.unchecked_into());
+    Ok(CameraSwap::Live)
+}
+
+// An input device as shown in the pickers
+#[derive(Clone, PartialEq)]
+pub struct InputDevice {
+    pub id: String,
+    pub label: String,
+}
+
+// (cameras, microphones); labels stay empty until permission has been granted once
+pub async fn enumerate_inputs() -> Result<(Vec<InputDevice>, Vec<InputDevice>), JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let media_devices = window.navigator().media_devices()?;
+    let list = wasm_bindgen_futures::JsFuture::from(media_devices.enumerate_devices()?).await?;
+    let (mut cameras, mut mics) = (Vec::new(), Vec::new());
+    for info in js_sys::Array::from(&list).iter() {
+        let info: web_sys::MediaDeviceInfo = info.unchecked_into();
+        let list = match info.kind() {
+            web_sys::MediaDeviceKind::Videoinput => &mut cameras,
+            web_sys::MediaDeviceKind::Audioinput => &mut mics,
+            _ => continue,
+        };
+        let label = match info.label() {
+            l if l.is_empty() => format!("Device {}", list.len() + 1),
+            l => l,
+        };
+        list.push(InputDevice { id: info.device_id(), label });
+    }
+    Ok((cameras, mics))
+}
+
+// What the Permissions API says about a device before we ask for a stream
+#[derive(Clone, Copy, PartialEq)]
+pub enum Access {
+    Granted,
+    // the browser will ask on the first getUserMedia
+    Prompt,
+    Denied,
+    // no Permissions API, or it doesn't know "camera"/"microphone" (Firefox, older Safari)
+    Unknown,
+}
+
+// `name` is "camera" or "microphone"; web-sys's PermissionName has neither, so the
+// descriptor is built by hand
+pub async fn query_access(name: &str) -> Access {
+    let Some(permissions) = web_sys::window().and_then(|w| w.navigator().permissions().ok()) else {
+        return Access::Unknown;
+    };
+    let descriptor = js_sys::Object::new();
+    if js_sys::Reflect::set(&descriptor, &"name".into(), &name.into()).is_err() {
+        return Access::Unknown;
+    }
+    let Ok(promise) = permissions.query(&descriptor) else {
+        return Access::Unknown;
+    };
+    match wasm_bindgen_futures::JsFuture::from(promise).await {
+        Ok(status) => match status.unchecked_into::<web_sys::PermissionStatus>().state() {
+            web_sys::PermissionState::Granted => Access::Granted,
+            web_sys::PermissionState::Prompt => Access::Prompt,
+            web_sys::PermissionState::Denied => Access::Denied,
+            _ => Access::Unknown,
+        },
+        Err(_) => Access::Unknown,
+    }
+}
+
+// One line of the pre-flight check for a kind of device, e.g. "Camera ready"
+pub fn preflight_line(device: &str, found: usize, access: Access) -> (bool, String) {
+    match (found, access) {
+        (0, _) => (false, format!("No {} found", device.to_lowercase())),
+        (_, Access::Granted) => (true, format!("{} ready", device)),
+        (_, Access::Denied) => (false, format!("{} blocked", device)),
+        (_, Access::Prompt) => (false, format!("{} permission needed", device)),
+        (_, Access::Unknown) => (true, format!("{} found", device)),
+    }
+}
+
+// A thrown JS value as one readable line, e.g. "InvalidStateError: The recorder is inactive"
+pub fn js_error_message(err: &JsValue) -> String {
+    if let Some(e) = err.dyn_ref::<web_sys::DomException>() {
+        format!("{}: {}", e.name(), e.message())
+    } else if let Some(e) = err.dyn_ref::<js_sys::Error>() {
+        format!("{}: {}", String::from(e.name()), String::from(e.message()))
+    } else if let Some(text) = err.as_string() {
+        text
+    } else {
+        format!("{:?}", err)
+    }
+}
+
+// Why we couldn't get a camera/mic stream
+#[derive(Clone, PartialEq)]
+pub enum MediaError {
+    PermissionDenied,
+    NoDevice,
+    Unsupported,
+    Other(String),
+}
+
+impl MediaError {
+    // map the DOMException name getUserMedia rejected with
+    fn from_exception_name(name: &str, message: String) -> Self {
+        match name {
+            "NotAllowedError" | "SecurityError" | "PermissionDeniedError" => MediaError::PermissionDenied,
+            "NotFoundError" | "OverconstrainedError" | "DevicesNotFoundError" => MediaError::NoDevice,
+            "NotSupportedError" | "TypeError" => MediaError::Unsupported,
+            _ => MediaError::Other(message),
+        }
+    }
+
+    pub fn from_js(err: &JsValue) -> Self {
+        if let Some(e) = err.dyn_ref::<web_sys::DomException>() {
+            Self::from_exception_name(&e.name(), e.message())
+        } else if let Some(e) = err.dyn_ref::<js_sys::Error>() {
+            Self::from_exception_name(&String::from(e.name()), String::from(e.message()))
+        } else {
+            MediaError::Other(format!("{:?}", err))
+        }
+    }
+}
+
+impl Display for MediaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MediaError::PermissionDenied => write!(
+                f,
+                "Camera/microphone access was blocked. Allow it in your browser or system settings, then retry."
+            ),
+            MediaError::NoDevice => write!(f, "No camera or microphone was found. Plug one in, then retry."),
+            MediaError::Unsupported => write!(f, "This browser can't capture camera/microphone input."),
+            MediaError::Other(msg) => write!(f, "Couldn't start the camera/microphone: {}", msg),
+        }
+    }
+}
+
+// Stop every track so the camera light goes off and the devices are released
+pub fn stop_tracks(stream: &MediaStream) {
+    for track in stream.get_tracks().iter() {
+        track.unchecked_into::<MediaStreamTrack>().stop();
+    }
+}
+
+// ① Request audio, and video unless this is an audio-only take
+pub async fn request_media_stream(capture: &CaptureConfig) -> Result<MediaStream, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let media_devices = window.navigator().media_devices()?;
+
+    let constraints = MediaStreamConstraints::new();
+    if capture.audio_only {
+        constraints.set_video(&JsValue::FALSE);
+    } else {
+        constraints.set_video(&video_constraint(capture.video_device.as_deref(), capture.resolution, capture.frame_rate));
+    }
+    constraints.set_audio(&track_constraint(capture.audio_device.as_deref()));
+
+    let media_promise = media_devices.get_user_media_with_constraints(&constraints)?;
+    let js_stream = wasm_bindgen_futures::JsFuture::from(media_promise).await?;
+    Ok(js_stream.unchecked_into())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordingStatus {
+    Idle,
+    Recording,
+    Paused,
+}
+// the recorder's own view of its state, which is what the UI should show
+impl From<RecordingState> for RecordingStatus {
+    fn from(state: RecordingState) -> Self {
+        match state {
+            RecordingState::Recording => RecordingStatus::Recording,
+            RecordingState::Paused => RecordingStatus::Paused,
+            _ => RecordingStatus::Idle,
+        }
+    }
+}
+
+// One poll of the recorder: what the UI should show instead of `seen` now that the
+// recorder is in `actual`, or `None` while the two agree. `seen` is brought up to date,
+// so a desync is corrected (and reported) once rather than on every poll. The recorder
+// wins: it can stop or pause by itself (a camera track ending stops it), and the UI
+// must never claim otherwise
+pub fn poll_status(seen: &mut RecordingStatus, actual: RecordingStatus) -> Option<RecordingStatus> {
+    (*seen != actual).then(|| {
+        *seen = actual.clone();
+        actual
+    })
+}
+
+impl Display for RecordingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RecordingStatus::Recording => "Recording",
+                RecordingStatus::Paused => "Paused",
+                RecordingStatus::Idle => "Idle",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hardware_friendly_types_come_first_when_preferred() {
+        let everything = |_: &str| true;
+        assert_eq!(pick_recording_type(true, false, everything), Some("video/mp4;codecs=avc1,mp4a.40.2"));
+        assert_eq!(pick_recording_type(false, false, everything), Some("video/webm;codecs=vp9,opus"));
+    }
+
+    #[test]
+    fn falls_back_through_the_list_to_what_is_supported() {
+        // no H.264 at all: even preferring hardware ends up on VP8
+        let vp8_only = |t: &str| t == "video/webm;codecs=vp8,opus";
+        assert_eq!(pick_recording_type(true, false, vp8_only), Some("video/webm;codecs=vp8,opus"));
+        // no VP8/VP9: software-first still finds the hardware list
+        let mp4_only = |t: &str| t == "video/mp4";
+        assert_eq!(pick_recording_type(false, false, mp4_only), Some("video/mp4"));
+    }
+
+    #[test]
+    fn audio_only_picks_only_audio_types() {
+        let everything = |_: &str| true;
+        assert_eq!(pick_recording_type(true, true, everything), Some("audio/webm;codecs=opus"));
+        let ogg_only = |t: &str| t.starts_with("audio/ogg");
+        assert_eq!(pick_recording_type(false, true, ogg_only), Some("audio/ogg;codecs=opus"));
+        // video support doesn't help an audio-only take
+        let video_only = |t: &str| t.starts_with("video/");
+        assert_eq!(pick_recording_type(false, true, video_only), None);
+    }
+
+    #[test]
+    fn nothing_supported_leaves_it_to_the_browser() {
+        let nothing = |_: &str| false;
+        assert_eq!(pick_recording_type(true, false, nothing), None);
+        assert_eq!(pick_recording_type(false, true, nothing), None);
+        assert_eq!(resolve_recording_type(Some("video/webm"), false, false, nothing), None);
+    }
+
+    #[test]
+    fn a_chosen_type_must_match_the_audio_only_setting() {
+        let everything = |_: &str| true;
+        assert_eq!(resolve_recording_type(Some("video/mp4"), false, false, everything).as_deref(), Some("video/mp4"));
+        // a video pick in audio-only mode falls back to our own audio choice
+        assert_eq!(
+            resolve_recording_type(Some("video/mp4"), false, true, everything).as_deref(),
+            Some("audio/webm;codecs=opus")
+        );
+    }
+
+    #[test]
+    fn extensions_ignore_codec_parameters() {
+        assert_eq!(extension_for_mime("video/mp4;codecs=avc1,mp4a.40.2"), "mp4");
+        assert_eq!(extension_for_mime("video/webm; codecs=vp9,opus"), "webm");
+        assert_eq!(extension_for_mime("audio/mp4"), "m4a");
+        assert_eq!(extension_for_mime("audio/webm;codecs=opus"), "weba");
+        assert_eq!(extension_for_mime("audio/ogg;codecs=opus"), "ogg");
+        assert_eq!(extension_for_mime("application/x-subrip"), "srt");
+    }
+
+    #[test]
+    fn segments_are_numbered_before_the_extension() {
+        assert_eq!(segment_filename("born-edited-20250301-142530.webm", 1), "born-edited-20250301-142530-part01.webm");
+        assert_eq!(segment_filename("take.m4a", 12), "take-part12.m4a");
+        assert_eq!(segment_filename("take", 3), "take-part03");
+    }
+
+    #[test]
+    fn unknown_or_missing_types_save_as_webm() {
+        assert_eq!(extension_for_mime(""), "webm");
+        assert_eq!(extension_for_mime("video/quicktime"), "webm");
+    }
+
+    // five one-second chunks, as the recorder's timeslice produces them
+    const ENDS: [f64; 5] = [1000.0, 2000.0, 3000.0, 4000.0, 5000.0];
+
+    #[test]
+    fn the_whole_range_keeps_every_chunk() {
+        assert_eq!(kept_chunks(&ENDS, 0.0, 5000.0), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn cuts_on_chunk_boundaries_leave_the_neighbours_out() {
+        // 2s..4s is exactly the third and fourth chunks, plus the header chunk
+        assert_eq!(kept_chunks(&ENDS, 2000.0, 4000.0), vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn cuts_inside_a_chunk_keep_all_of_it() {
+        assert_eq!(kept_chunks(&ENDS, 1999.0, 4001.0), vec![0, 1, 2, 3, 4]);
+        assert_eq!(kept_chunks(&ENDS, 2001.0, 2999.0), vec![0, 2]);
+    }
+
+    #[test]
+    fn the_header_chunk_is_always_kept() {
+        assert_eq!(kept_chunks(&ENDS, 4000.0, 5000.0), vec![0, 4]);
+        assert_eq!(kept_chunks(&ENDS, 0.0, 1000.0), vec![0]);
+    }
+
+    #[test]
+    fn an_empty_or_backwards_range_keeps_only_the_header() {
+        assert_eq!(kept_chunks(&ENDS, 3000.0, 3000.0), vec![0]);
+        assert_eq!(kept_chunks(&ENDS, 4500.0, 1500.0), vec![0]);
+        assert_eq!(kept_chunks(&[], 0.0, 1000.0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn a_same_size_camera_switch_keeps_the_take_going() {
+        assert_eq!(plan_camera_swap(true, true, false), SwapPlan::Keep);
+        assert_eq!(plan_camera_swap(true, true, true), SwapPlan::Keep);
+        // the canvas hides the new size from the recorder
+        assert_eq!(plan_camera_swap(true, false, true), SwapPlan::Keep);
+    }
+
+    #[test]
+    fn a_track_is_replaced_only_under_the_compositor() {
+        assert_eq!(plan_camera_swap(false, true, true), SwapPlan::ReplaceTrack);
+        assert_eq!(plan_camera_swap(false, true, false), SwapPlan::Restart);
+    }
+
+    #[test]
+    fn a_new_frame_size_restarts_a_recorder_reading_the_stream() {
+        assert_eq!(plan_camera_swap(true, false, false), SwapPlan::Restart);
+        assert_eq!(plan_camera_swap(false, false, false), SwapPlan::Restart);
+    }
+
+    #[test]
+    fn a_take_cut_short_by_an_ended_track_is_reconciled_once() {
+        // Record was pressed and the recorder is running
+        let mut seen = RecordingStatus::Recording;
+        assert_eq!(poll_status(&mut seen, RecordingState::Recording.into()), None);
+        // the camera is unplugged: its track ends and the recorder stops by itself
+        assert_eq!(poll_status(&mut seen, RecordingState::Inactive.into()), Some(RecordingStatus::Idle));
+        assert_eq!(seen, RecordingStatus::Idle);
+        // later polls find nothing to fix, however long onstop takes to hand the take over
+        assert_eq!(poll_status(&mut seen, RecordingState::Inactive.into()), None);
+        // the take arrives and its handler sets Idle as well; the next take starts cleanly
+        seen = RecordingStatus::Recording;
+        assert_eq!(poll_status(&mut seen, RecordingState::Recording.into()), None);
+    }
+
+    #[test]
+    fn a_take_handed_over_before_the_poll_needs_no_correction() {
+        // onstop ran first and the UI already shows Idle
+        let mut seen = RecordingStatus::Idle;
+        assert_eq!(poll_status(&mut seen, RecordingState::Inactive.into()), None);
+    }
+
+    #[test]
+    fn a_recorder_paused_by_the_browser_shows_as_paused() {
+        let mut seen = RecordingStatus::Recording;
+        assert_eq!(poll_status(&mut seen, RecordingState::Paused.into()), Some(RecordingStatus::Paused));
+        assert_eq!(poll_status(&mut seen, RecordingState::Recording.into()), Some(RecordingStatus::Recording));
+        assert_eq!(seen, RecordingStatus::Recording);
+    }
+}
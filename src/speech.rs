@@ -0,0 +1,262 @@
+// Speaking pace: the rolling words-per-minute math, the clock it runs on, and
+// the browser recognizer that feeds it.
+use std::collections::VecDeque;
+use wasm_bindgen::JsValue;
+use web_sys::SpeechRecognition;
+
+// how much recent speech the live WPM looks at; shorter reacts faster but jitters more
+pub const WPM_WINDOW_SECS: f64 = 10.0;
+
+// Words heard over time, and the pace they add up to over the last `WPM_WINDOW_SECS`.
+// Plain Rust, so it's driven by whatever clock the caller hands it.
+pub struct WpmTracker {
+    // (timestamp, new words) for the recent past
+    log: VecDeque<(f64, usize)>,
+    started_ms: f64,
+}
+
+impl WpmTracker {
+    pub fn new(started_ms: f64) -> Self {
+        WpmTracker { log: VecDeque::new(), started_ms }
+    }
+
+    pub fn record_words(&mut self, count: usize, now_ms: f64) {
+        if count > 0 {
+            self.log.push_back((now_ms, count));
+        }
+    }
+
+    // Words per minute over the window, dropping older entries as it goes.
+    // Before a full window has passed it uses the time since start; `None` for the first second.
+    pub fn current_wpm(&mut self, now_ms: f64) -> Option<f64> {
+        let window_ms = WPM_WINDOW_SECS * 1000.0;
+        while self.log.front().is_some_and(|(t, _)| now_ms - t > window_ms) {
+            self.log.pop_front();
+        }
+        let span_secs = (now_ms - self.started_ms).min(window_ms) / 1000.0;
+        if span_secs <= 1.0 {
+            return None;
+        }
+        let words: usize = self.log.iter().map(|(_, n)| n).sum();
+        Some(words as f64 / span_secs * 60.0)
+    }
+}
+
+// by default, results the recognizer is less sure of than this don't count towards WPM
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.5;
+
+// How many words of a session's results, as (transcript, confidence, is_final), count
+// towards WPM. Interim guesses get revised and seldom carry a confidence, so only final
+// results count, and only those at least `min_confidence` sure. A confidence of exactly
+// 0 means the browser didn't give one, which isn't held against the result.
+pub fn countable_words<'a>(results: impl IntoIterator<Item = (&'a str, f32, bool)>, min_confidence: f32) -> usize {
+    results
+        .into_iter()
+        .filter(|(_, confidence, is_final)| *is_final && (*confidence == 0.0 || *confidence >= min_confidence))
+        .map(|(text, _, _)| text.split_whitespace().count())
+        .sum()
+}
+
+// Countable words in each finalized result of one recognition session, by result
+// index. Every event resends the session's whole results list, but results below its
+// `resultIndex` can't change any more, so only the rest need reading.
+#[derive(Default)]
+pub struct SessionWords {
+    per_result: Vec<usize>,
+    // most ever counted, so a resent result can't be counted twice
+    counted: usize,
+}
+
+impl SessionWords {
+    // Take in an event's results from `result_index` on, as (transcript, confidence, is_final).
+    // Returns how many countable words became final with it.
+    pub fn update<'a>(
+        &mut self,
+        result_index: usize,
+        fresh: impl IntoIterator<Item = (&'a str, f32, bool)>,
+        min_confidence: f32,
+    ) -> usize {
+        self.per_result.truncate(result_index);
+        // final results come first; whatever follows is still being revised
+        for result in fresh.into_iter().take_while(|(_, _, is_final)| *is_final) {
+            self.per_result.push(countable_words([result], min_confidence));
+        }
+        let total: usize = self.per_result.iter().sum();
+        let new = total.saturating_sub(self.counted);
+        self.counted = self.counted.max(total);
+        new
+    }
+}
+
+// Wall-clock time with the recording's paused stretches cut out, so a break
+// doesn't read as a long silence to the WPM window
+#[derive(Default)]
+pub struct ActiveClock {
+    paused_total_ms: f64,
+    paused_since: Option<f64>,
+}
+
+impl ActiveClock {
+    pub fn pause(&mut self, now_ms: f64) {
+        self.paused_since.get_or_insert(now_ms);
+    }
+
+    pub fn resume(&mut self, now_ms: f64) {
+        if let Some(since) = self.paused_since.take() {
+            self.paused_total_ms += now_ms - since;
+        }
+    }
+
+    // `now_ms` on the active timeline; stands still while paused
+    pub fn now(&self, now_ms: f64) -> f64 {
+        let paused_now = self.paused_since.map_or(0.0, |since| now_ms - since);
+        now_ms - self.paused_total_ms - paused_now
+    }
+}
+
+// a session shorter than this counts as ending "immediately"
+pub const SPEECH_QUICK_END_MS: f64 = 1000.0;
+pub const SPEECH_MAX_QUICK_ENDS: u32 = 5;
+
+// (BCP 47 tag, label) pairs offered for speech recognition
+pub const SPEECH_LANGUAGES: &[(&str, &str)] = &[
+    ("en-US", "English (US)"),
+    ("en-GB", "English (UK)"),
+    ("es-ES", "Español"),
+    ("fr-FR", "Français"),
+    ("de-DE", "Deutsch"),
+    ("hi-IN", "हिन्दी"),
+];
+
+// The running recognizer, and whether we still want it running
+pub struct Recognizer {
+    pub recog: SpeechRecognition,
+    // cleared on an intentional stop, or when restarts keep failing
+    pub keep_alive: bool,
+    // sessions in a row that ended almost as soon as they started
+    pub quick_ends: u32,
+    // the current session's finalized words; replaced when a new session starts
+    pub words: SessionWords,
+    // between a start() the browser accepted and the session's end event; starting
+    // again in between throws InvalidStateError
+    pub started: bool,
+}
+
+impl Recognizer {
+    // A continuous recognizer with interim results, not yet started.
+    // Fails where the browser has no SpeechRecognition (e.g. Firefox).
+    pub fn new(lang: &str) -> Result<Recognizer, JsValue> {
+        let recog = SpeechRecognition::new()?;
+        recog.set_continuous(true)?;
+        recog.set_interim_results(true);
+        recog.set_lang(lang);
+        Ok(Recognizer { recog, keep_alive: true, quick_ends: 0, words: SessionWords::default(), started: false })
+    }
+
+    // Start a session unless one is already running. The browser may still refuse
+    // (e.g. no mic permission), which comes back as the error rather than a panic.
+    pub fn start(&mut self) -> Result<(), JsValue> {
+        if self.started {
+            return Ok(());
+        }
+        self.recog.start()?;
+        self.started = true;
+        Ok(())
+    }
+
+    // Feed an event's results from `result_index` on into `tracker`, counting only
+    // what's newly final
+    pub fn heard<'a>(
+        &mut self,
+        result_index: usize,
+        fresh: impl IntoIterator<Item = (&'a str, f32, bool)>,
+        min_confidence: f32,
+        tracker: &mut WpmTracker,
+        now_ms: f64,
+    ) {
+        tracker.record_words(self.words.update(result_index, fresh, min_confidence), now_ms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_reading_before_a_second_has_passed() {
+        let mut tracker = WpmTracker::new(5_000.0);
+        tracker.record_words(3, 5_000.0);
+        assert_eq!(tracker.current_wpm(5_000.0), None);
+        assert_eq!(tracker.current_wpm(5_900.0), None);
+    }
+
+    #[test]
+    fn steady_speech_reads_its_rate() {
+        // two words a second for 30s is 120 WPM, whichever part of the window we look at
+        let mut tracker = WpmTracker::new(0.0);
+        for i in 1..=60 {
+            tracker.record_words(1, i as f64 * 500.0);
+            if i % 10 == 0 {
+                let wpm = tracker.current_wpm(i as f64 * 500.0).unwrap();
+                assert!((wpm - 120.0).abs() < 7.0, "{wpm} at {}ms", i * 500);
+            }
+        }
+    }
+
+    #[test]
+    fn a_burst_ages_out_of_the_window() {
+        let mut tracker = WpmTracker::new(0.0);
+        tracker.record_words(20, 2_000.0);
+        // 20 words in the first 4 seconds
+        assert_eq!(tracker.current_wpm(4_000.0), Some(300.0));
+        // still inside the window, now spread over all of it
+        assert_eq!(tracker.current_wpm(10_000.0), Some(120.0));
+        // then silence
+        assert_eq!(tracker.current_wpm(12_500.0), Some(0.0));
+    }
+
+    #[test]
+    fn only_confident_final_words_count() {
+        let results = [
+            ("so today we are", 0.9, true),
+            ("uh hmm", 0.2, true),
+            ("going to look at", 0.0, true),
+            ("the next", 0.0, false),
+        ];
+        assert_eq!(countable_words(results, 0.0), 10);
+        assert_eq!(countable_words(results, DEFAULT_MIN_CONFIDENCE), 8);
+    }
+
+    #[test]
+    fn filtering_keeps_noise_out_of_the_pace() {
+        // the same 10s of speech, one result a second, every other one low-confidence noise
+        let results: Vec<(&str, f32, bool)> =
+            (0..10).map(|i| if i % 2 == 0 { ("one two three", 0.9, true) } else { ("um", 0.1, true) }).collect();
+        let pace = |min_confidence| {
+            let mut tracker = WpmTracker::new(0.0);
+            let mut seen = 0;
+            for i in 1..=results.len() {
+                let words = countable_words(results[..i].iter().copied(), min_confidence);
+                tracker.record_words(words - seen, i as f64 * 1000.0);
+                seen = words;
+            }
+            tracker.current_wpm(10_000.0).unwrap()
+        };
+        assert_eq!(pace(0.0), 120.0);
+        assert_eq!(pace(DEFAULT_MIN_CONFIDENCE), 90.0);
+    }
+
+    #[test]
+    fn each_final_result_counts_once() {
+        let mut words = SessionWords::default();
+        // an interim guess, revised, then settled
+        assert_eq!(words.update(0, [("hello", 0.0, false)], 0.0), 0);
+        assert_eq!(words.update(0, [("hello there", 0.0, false)], 0.0), 0);
+        assert_eq!(words.update(0, [("hello there", 0.9, true)], 0.0), 2);
+        // the next utterance; the settled one is below resultIndex and not resent
+        assert_eq!(words.update(1, [("how are", 0.0, false)], 0.0), 0);
+        assert_eq!(words.update(1, [("how are you", 0.8, true), ("I'm", 0.0, false)], 0.0), 3);
+        // a browser that resends everything from the start counts nothing twice
+        assert_eq!(words.update(0, [("hello there", 0.9, true), ("how are you", 0.8, true)], 0.0), 0);
+    }
+}
@@ -0,0 +1,348 @@
+// Takes kept across reloads, in IndexedDB. Each take is two records: its details
+// (rating, trim, where it was saved…) in `takes`, rewritten whenever they change, and
+// what it recorded in `media`, written once. `TakeStorage` decides which takes are
+// kept and whether their media goes with them.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use web_sys::{Blob, IdbDatabase, IdbRequest, IdbTransactionMode};
+
+const DB_NAME: &str = "born-ed-takes";
+const DB_VERSION: u32 = 1;
+const DETAILS: &str = "takes";
+const MEDIA: &str = "media";
+
+// past this share of what the browser lets the page store, warn before saving takes fails
+pub const QUOTA_WARN_RATIO: f64 = 0.8;
+
+// What of each take is kept in the browser for next time
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum TakeStorage {
+    // nothing; takes go when the page does
+    #[default]
+    Off,
+    // every take with its recording, so it survives a reload or a crash
+    FullBlob,
+    // takes saved to disk, as their details and the file's path; next to no browser storage
+    PathOnly,
+}
+
+impl TakeStorage {
+    pub const ALL: [TakeStorage; 3] = [TakeStorage::Off, TakeStorage::FullBlob, TakeStorage::PathOnly];
+
+    // What this keeps of a take, given where (if anywhere) it has been saved to disk
+    pub fn keeps(self, saved_to: Option<&str>) -> Kept {
+        match (self, saved_to) {
+            (TakeStorage::Off, _) | (TakeStorage::PathOnly, None) => Kept::Nothing,
+            (TakeStorage::FullBlob, _) => Kept::WithMedia,
+            (TakeStorage::PathOnly, Some(_)) => Kept::Details,
+        }
+    }
+}
+
+impl Display for TakeStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TakeStorage::Off => "Don't keep takes",
+            TakeStorage::FullBlob => "Keep takes with their recordings",
+            TakeStorage::PathOnly => "Keep saved takes by file path",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kept {
+    Nothing,
+    Details,
+    WithMedia,
+}
+
+// A take's record in `takes`: all of it but the recording
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoredTake {
+    pub number: u32,
+    pub mime: String,
+    pub duration_secs: f64,
+    pub trim: (u32, u32),
+    pub rating: u8,
+    pub segment: Option<u32>,
+    pub wpm_samples: Vec<(u32, u32)>,
+    // where the desktop app last saved it, if it has
+    pub path: Option<String>,
+}
+
+// What the store holds, as of the last load or write
+#[derive(Default)]
+pub struct Stored {
+    pub details: HashMap<u32, StoredTake>,
+    pub media: HashSet<u32>,
+}
+
+impl Stored {
+    // take note that `plan` has been written
+    pub fn apply(&mut self, plan: SyncPlan) {
+        for number in plan.delete_details {
+            self.details.remove(&number);
+        }
+        for number in plan.delete_media {
+            self.media.remove(&number);
+        }
+        self.details.extend(plan.put_details.into_iter().map(|take| (take.number, take)));
+        self.media.extend(plan.put_media);
+    }
+}
+
+// The writes that bring the store in line with the takes
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncPlan {
+    pub put_details: Vec<StoredTake>,
+    pub put_media: Vec<u32>,
+    pub delete_details: Vec<u32>,
+    pub delete_media: Vec<u32>,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        *self == SyncPlan::default()
+    }
+}
+
+// Compare what is stored with each take and how much of it should be: details are
+// rewritten only when they changed, media written only once, and anything no
+// longer wanted (a deleted take, a stricter setting) removed
+pub fn plan_sync(stored: &Stored, wanted: &[(StoredTake, Kept)]) -> SyncPlan {
+    let mut plan = SyncPlan::default();
+    let mut keep = HashSet::new();
+    for (take, kept) in wanted {
+        let number = take.number;
+        if *kept == Kept::Nothing {
+            continue;
+        }
+        keep.insert(number);
+        if stored.details.get(&number) != Some(take) {
+            plan.put_details.push(take.clone());
+        }
+        match (*kept == Kept::WithMedia, stored.media.contains(&number)) {
+            (true, false) => plan.put_media.push(number),
+            (false, true) => plan.delete_media.push(number),
+            _ => {}
+        }
+    }
+    plan.delete_details.extend(stored.details.keys().filter(|n| !keep.contains(n)));
+    plan.delete_media.extend(stored.media.iter().filter(|n| !keep.contains(n)));
+    plan.delete_details.sort_unstable();
+    plan.delete_media.sort_unstable();
+    plan
+}
+
+// Whether `usage` bytes is getting close to the `quota` the browser reported
+pub fn near_quota(usage: f64, quota: f64) -> bool {
+    quota > 0.0 && usage >= quota * QUOTA_WARN_RATIO
+}
+
+// resolves with the request's result once it succeeds; made before awaiting anything
+// else, so the request can't finish unheard
+fn done(request: &IdbRequest) -> wasm_bindgen_futures::JsFuture {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let on_success = {
+            let request = request.clone();
+            Closure::once_into_js(move || {
+                let _ = resolve.call1(&JsValue::NULL, &request.result().unwrap_or(JsValue::UNDEFINED));
+            })
+        };
+        let on_error = {
+            let request = request.clone();
+            Closure::once_into_js(move || {
+                let err = request.error().ok().flatten().map_or(JsValue::NULL, JsValue::from);
+                let _ = reject.call1(&JsValue::NULL, &err);
+            })
+        };
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    wasm_bindgen_futures::JsFuture::from(promise)
+}
+
+pub async fn open() -> Result<IdbDatabase, JsValue> {
+    let factory = web_sys::window()
+        .ok_or_else(|| JsValue::from_str("no window"))?
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from_str("IndexedDB isn't available"))?;
+    let request = factory.open_with_u32(DB_NAME, DB_VERSION)?;
+    // first open: make the stores
+    let on_upgrade = {
+        let request = request.clone();
+        Closure::once_into_js(move || {
+            let Ok(db) = request.result().map(|db| db.unchecked_into::<IdbDatabase>()) else {
+                return;
+            };
+            let details = web_sys::IdbObjectStoreParameters::new();
+            details.set_key_path(&JsValue::from_str("number"));
+            if !db.object_store_names().contains(DETAILS) {
+                let _ = db.create_object_store_with_optional_parameters(DETAILS, &details);
+            }
+            if !db.object_store_names().contains(MEDIA) {
+                let _ = db.create_object_store(MEDIA);
+            }
+        })
+    };
+    request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+    Ok(done(&request).await?.unchecked_into())
+}
+
+// Every stored take, with its chunks (`(ends_at, blob)`, as the recorder made them)
+// if its media was kept, oldest first
+pub async fn load(db: &IdbDatabase) -> Result<Vec<(StoredTake, Option<Vec<(f64, Blob)>>)>, JsValue> {
+    let stores = js_sys::Array::of2(&DETAILS.into(), &MEDIA.into());
+    let tx = db.transaction_with_str_sequence_and_mode(&stores, IdbTransactionMode::Readonly)?;
+    let details = done(&tx.object_store(DETAILS)?.get_all()?);
+    let media_store = tx.object_store(MEDIA)?;
+    let keys = done(&media_store.get_all_keys()?);
+    let media = done(&media_store.get_all()?);
+    let details = js_sys::Array::from(&details.await?);
+    let keys = js_sys::Array::from(&keys.await?);
+    let media = js_sys::Array::from(&media.await?);
+
+    let mut chunks_of: HashMap<u32, Vec<(f64, Blob)>> = HashMap::new();
+    for (key, chunks) in keys.iter().zip(media.iter()) {
+        let Some(number) = key.as_f64() else { continue };
+        let chunks = js_sys::Array::from(&chunks)
+            .iter()
+            .map(|pair| {
+                let pair = js_sys::Array::from(&pair);
+                (pair.get(0).as_f64().unwrap_or(0.0), pair.get(1).unchecked_into::<Blob>())
+            })
+            .collect();
+        chunks_of.insert(number as u32, chunks);
+    }
+    let mut takes = Vec::new();
+    for record in details.iter() {
+        match serde_wasm_bindgen::from_value::<StoredTake>(record) {
+            Ok(take) => {
+                let chunks = chunks_of.remove(&take.number);
+                takes.push((take, chunks));
+            }
+            Err(err) => gloo::console::warn!(format!("skipping a stored take: {err}")),
+        }
+    }
+    takes.sort_by_key(|(take, _)| take.number);
+    Ok(takes)
+}
+
+// Carry out `plan` in one transaction; `chunks_of` has the media for `put_media`
+pub async fn write(
+    db: &IdbDatabase,
+    plan: &SyncPlan,
+    chunks_of: impl Fn(u32) -> Option<Vec<(f64, Blob)>>,
+) -> Result<(), JsValue> {
+    let stores = js_sys::Array::of2(&DETAILS.into(), &MEDIA.into());
+    let tx = db.transaction_with_str_sequence_and_mode(&stores, IdbTransactionMode::Readwrite)?;
+    let details = tx.object_store(DETAILS)?;
+    let media = tx.object_store(MEDIA)?;
+    // every request is queued before the first await, or the transaction would commit early
+    let mut requests: Vec<IdbRequest> = Vec::new();
+    for take in &plan.put_details {
+        let record = serde_wasm_bindgen::to_value(take).map_err(|err| JsValue::from_str(&err.to_string()))?;
+        requests.push(details.put(&record)?);
+    }
+    for &number in &plan.put_media {
+        let Some(chunks) = chunks_of(number) else { continue };
+        let record: js_sys::Array = chunks
+            .iter()
+            .map(|(ends_at, blob)| js_sys::Array::of2(&(*ends_at).into(), blob))
+            .collect();
+        requests.push(media.put_with_key(&record, &number.into())?);
+    }
+    for &number in &plan.delete_details {
+        requests.push(details.delete(&number.into())?);
+    }
+    for &number in &plan.delete_media {
+        requests.push(media.delete(&number.into())?);
+    }
+    let pending: Vec<_> = requests.iter().map(done).collect();
+    for request in pending {
+        request.await?;
+    }
+    Ok(())
+}
+
+// (bytes used, bytes allowed) for this origin, where the browser says
+pub async fn estimate() -> Option<(f64, f64)> {
+    let storage = web_sys::window()?.navigator().storage();
+    let estimate: web_sys::StorageEstimate =
+        wasm_bindgen_futures::JsFuture::from(storage.estimate().ok()?).await.ok()?.unchecked_into();
+    Some((estimate.get_usage()?, estimate.get_quota()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(number: u32, rating: u8) -> StoredTake {
+        StoredTake {
+            number,
+            mime: "video/webm".into(),
+            duration_secs: 12.0,
+            trim: (0, 12),
+            rating,
+            segment: None,
+            wpm_samples: Vec::new(),
+            path: None,
+        }
+    }
+
+    fn stored(takes: &[StoredTake], media: &[u32]) -> Stored {
+        Stored {
+            details: takes.iter().map(|t| (t.number, t.clone())).collect(),
+            media: media.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn path_only_keeps_just_the_takes_saved_to_disk() {
+        assert_eq!(TakeStorage::PathOnly.keeps(None), Kept::Nothing);
+        assert_eq!(TakeStorage::PathOnly.keeps(Some("/v/take.webm")), Kept::Details);
+        assert_eq!(TakeStorage::FullBlob.keeps(None), Kept::WithMedia);
+        assert_eq!(TakeStorage::Off.keeps(Some("/v/take.webm")), Kept::Nothing);
+    }
+
+    #[test]
+    fn a_new_take_is_written_with_its_media_once() {
+        let new = take(1, 0);
+        let plan = plan_sync(&Stored::default(), &[(new.clone(), Kept::WithMedia)]);
+        assert_eq!(plan, SyncPlan { put_details: vec![new.clone()], put_media: vec![1], ..SyncPlan::default() });
+        // rating it rewrites the details and leaves the media alone
+        let rated = take(1, 4);
+        let plan = plan_sync(&stored(&[new], &[1]), &[(rated.clone(), Kept::WithMedia)]);
+        assert_eq!(plan, SyncPlan { put_details: vec![rated.clone()], ..SyncPlan::default() });
+        assert!(plan_sync(&stored(std::slice::from_ref(&rated), &[1]), &[(rated, Kept::WithMedia)]).is_empty());
+    }
+
+    #[test]
+    fn deleted_takes_and_dropped_media_are_removed() {
+        let (one, two) = (take(1, 0), take(2, 0));
+        let was = stored(&[one.clone(), two], &[1, 2]);
+        // take 2 deleted, and the setting now keeps only details
+        let plan = plan_sync(&was, &[(one, Kept::Details)]);
+        assert_eq!(plan, SyncPlan { delete_details: vec![2], delete_media: vec![1, 2], ..SyncPlan::default() });
+    }
+
+    #[test]
+    fn turning_storage_off_clears_everything() {
+        let (one, two) = (take(1, 0), take(2, 0));
+        let was = stored(&[one.clone(), two.clone()], &[1]);
+        let plan = plan_sync(&was, &[(one, Kept::Nothing), (two, Kept::Nothing)]);
+        assert_eq!(plan, SyncPlan { delete_details: vec![1, 2], delete_media: vec![1], ..SyncPlan::default() });
+    }
+
+    #[test]
+    fn the_warning_starts_near_the_quota() {
+        assert!(!near_quota(10.0, 100.0));
+        assert!(near_quota(80.0, 100.0));
+        // a browser that reports no quota can't be near it
+        assert!(!near_quota(80.0, 0.0));
+    }
+}